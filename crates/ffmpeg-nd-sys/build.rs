@@ -125,6 +125,10 @@ fn main() {
 
     let mut libs = features.clone();
     libs.push("avutil");
+    // libsrt encrypts with OpenSSL.
+    if features.contains(&"srt") {
+        libs.extend(["ssl", "crypto"]);
+    }
 
     let lib_path = format!("{}/vcpkg/installed/x64-linux/lib", manifest_dir);
     let include_path = format!("{}/vcpkg/installed/x64-linux/include", manifest_dir);
@@ -140,11 +144,19 @@ fn main() {
         println!("cargo:rustc-link-lib=static={}", lib);
     }
 
-    let bindings = bindgen::Builder::default()
+    let mut builder = bindgen::Builder::default()
         .clang_arg("-Ivcpkg/installed/x64-linux/include")
         .header(format!("{}/libavcodec/avcodec.h", include_path))
         .header(format!("{}/libavformat/avformat.h", include_path))
-        .header(format!("{}/libavutil/opt.h", include_path))
+        .header(format!("{}/libavutil/opt.h", include_path));
+    if features.contains(&"swresample") {
+        builder = builder.header(format!("{}/libswresample/swresample.h", include_path));
+    }
+    if features.contains(&"swscale") {
+        builder = builder.header(format!("{}/libswscale/swscale.h", include_path));
+    }
+
+    let bindings = builder
         .parse_callbacks(Box::new(Callbacks))
         .generate()
         .expect("Unable to generate bindings");
@@ -3,6 +3,8 @@ mod context;
 mod id;
 mod packet;
 
+pub use codec::Codec;
 pub(crate) use codec::*;
 pub(crate) use context::Context;
+pub(crate) use id::Id;
 pub(crate) use packet::Packet;
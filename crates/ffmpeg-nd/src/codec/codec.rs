@@ -39,6 +39,11 @@ impl Codec {
     pub fn is_decoder(&self) -> bool {
         unsafe { av_codec_is_decoder(self.ptr) != 0 }
     }
+
+    /// Whether the `AV_CODEC_CAP_*` flag is set.
+    pub fn has_capability(&self, capability: u32) -> bool {
+        self.capabilities as u32 & capability != 0
+    }
 }
 
 impl From<*const AVCodec> for Codec {
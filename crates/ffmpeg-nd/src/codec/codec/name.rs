@@ -1,6 +1,6 @@
 use super::super::id::Id;
 use crate::codec::find_encoder_by_name;
-use crate::nd_media::VideoContextBuilder;
+use crate::nd_media::{AudioContextBuilder, VideoContextBuilder};
 use crate::util::Error;
 
 pub enum Name {
@@ -10,6 +10,10 @@ pub enum Name {
     LibX264,
     #[cfg(feature = "openh264")]
     LibOpenH264,
+    Aac,
+    Flac,
+    #[cfg(feature = "opus")]
+    LibOpus,
 }
 
 impl Name {
@@ -21,6 +25,10 @@ impl Name {
             Name::LibX264 => "libx264",
             #[cfg(feature = "openh264")]
             Name::LibOpenH264 => "libopenh264",
+            Name::Aac => "aac",
+            Name::Flac => "flac",
+            #[cfg(feature = "opus")]
+            Name::LibOpus => "libopus",
         }
     }
 
@@ -31,6 +39,14 @@ impl Name {
             Err(format!("Could not find encoder for '{}'", self.name()).into())
         }
     }
+
+    pub fn try_into_audio_encode_context_builder(self) -> Result<AudioContextBuilder, Error> {
+        if let Some(encoder) = find_encoder_by_name(&self) {
+            Ok(AudioContextBuilder::new(encoder))
+        } else {
+            Err(format!("Could not find encoder for '{}'", self.name()).into())
+        }
+    }
 }
 
 impl AsRef<str> for Name {
@@ -48,6 +64,10 @@ impl From<Name> for Id {
             Name::LibX264 => Id::H264,
             #[cfg(feature = "openh264")]
             Name::LibOpenH264 => Id::H264,
+            Name::Aac => Id::AAC,
+            Name::Flac => Id::FLAC,
+            #[cfg(feature = "opus")]
+            Name::LibOpus => Id::OPUS,
         }
     }
 }
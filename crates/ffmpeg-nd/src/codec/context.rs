@@ -1,6 +1,9 @@
 use super::codec::Codec;
 use super::packet::Packet;
-use crate::util::{Error, Frame, MediaType, PixelFormat, Rational, SearchFlag};
+use crate::util::{
+    ChannelLayout, ColorRange, ColorSpace, Dictionary, Error, Frame, MediaType, PixelFormat,
+    Rational, SampleFormat, SearchFlag,
+};
 use core::ops::{Deref, DerefMut};
 use ffi::*;
 use std::ffi::CString;
@@ -41,6 +44,30 @@ impl Context {
         }
     }
 
+    #[inline(always)]
+    pub fn send_packet(&mut self, packet: &Packet) -> Result<(), Error> {
+        match unsafe { avcodec_send_packet(self.ptr, packet.as_ptr()) } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    #[inline(always)]
+    pub fn send_packet_eof(&mut self) -> Result<(), Error> {
+        match unsafe { avcodec_send_packet(self.ptr, core::ptr::null()) } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    #[inline(always)]
+    pub fn receive_frame(&mut self, frame: &mut Frame) -> Result<(), Error> {
+        match unsafe { avcodec_receive_frame(self.ptr, frame.as_mut_ptr()) } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
     #[inline(always)]
     pub fn codec_type(&self) -> MediaType {
         self.codec_type.into()
@@ -54,9 +81,14 @@ impl Context {
 
 impl Context {
     pub fn open(&mut self) -> Result<(), Error> {
-        match unsafe { avcodec_open2(self.ptr, self.codec, core::ptr::null_mut()) } {
+        self.open_with_options(Dictionary::new()).map(drop)
+    }
+
+    /// Open with codec and private codec options. Returns the options the codec did not use.
+    pub fn open_with_options(&mut self, mut options: Dictionary) -> Result<Dictionary, Error> {
+        match unsafe { avcodec_open2(self.ptr, self.codec, options.as_mut_ptr_ptr()) } {
             e if e < 0 => Err(e.into()),
-            _ => Ok(()),
+            _ => Ok(options),
         }
     }
 
@@ -76,6 +108,10 @@ impl Context {
         self.height = value.into();
     }
 
+    pub fn frame_rate(&self) -> Rational {
+        self.framerate.into()
+    }
+
     pub fn set_frame_rate<R: Into<Rational>>(&mut self, value: R) {
         self.framerate = value.into().into();
     }
@@ -88,6 +124,10 @@ impl Context {
         self.time_base = value.into().into();
     }
 
+    pub fn set_pkt_timebase<R: Into<Rational>>(&mut self, value: R) {
+        self.pkt_timebase = value.into().into();
+    }
+
     pub fn set_bit_rate(&mut self, value: u32) {
         self.bit_rate = value.into();
     }
@@ -108,6 +148,59 @@ impl Context {
         self.pix_fmt = value.into().into();
     }
 
+    pub fn colorspace(&self) -> ColorSpace {
+        self.colorspace.into()
+    }
+
+    pub fn set_colorspace<T: Into<ColorSpace>>(&mut self, value: T) {
+        self.colorspace = value.into().into();
+    }
+
+    pub fn color_range(&self) -> ColorRange {
+        self.color_range.into()
+    }
+
+    pub fn set_color_range<T: Into<ColorRange>>(&mut self, value: T) {
+        self.color_range = value.into().into();
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate.try_into().unwrap()
+    }
+
+    pub fn set_sample_rate(&mut self, value: u32) {
+        self.sample_rate = value.try_into().unwrap();
+    }
+
+    pub fn sample_fmt(&self) -> SampleFormat {
+        self.sample_fmt.into()
+    }
+
+    pub fn set_sample_fmt<T: Into<SampleFormat>>(&mut self, value: T) {
+        self.sample_fmt = value.into().into();
+    }
+
+    pub fn ch_layout(&self) -> ChannelLayout {
+        (&self.ch_layout as *const AVChannelLayout).into()
+    }
+
+    pub fn set_ch_layout(&mut self, value: &ChannelLayout) -> Result<(), Error> {
+        value.copy_to(&mut self.ch_layout)
+    }
+
+    /// Number of samples per audio frame. Codecs with a variable frame size report 0.
+    pub fn frame_size(&self) -> u32 {
+        self.frame_size.try_into().unwrap()
+    }
+
+    /// Number of samples to put in each audio frame sent to the encoder.
+    pub fn samples_per_frame(&self) -> usize {
+        match self.frame_size() {
+            0 => 1024,
+            n => n as usize,
+        }
+    }
+
     pub fn set_opt(&mut self, name: &str, val: &str, flag: SearchFlag) -> Result<(), Error> {
         let n = CString::new(name).unwrap_or_default();
         let v = CString::new(val).unwrap_or_default();
@@ -138,6 +231,8 @@ impl Drop for Context {
     }
 }
 
+unsafe impl Send for Context {}
+
 impl From<Codec> for Context {
     fn from(codec: Codec) -> Self {
         Context {
@@ -1,3 +1,5 @@
+#![allow(non_upper_case_globals)]
+
 use ffi::*;
 use std::borrow::Cow;
 use std::ffi::CStr;
@@ -5,6 +7,11 @@ use std::ffi::CStr;
 pub enum Id {
     AV1,
     H264,
+    AAC,
+    FLAC,
+    OPUS,
+    /// Any codec id without a dedicated variant, e.g. read from stream parameters.
+    Other(AVCodecID),
 }
 
 impl Id {
@@ -18,6 +25,23 @@ impl AsRef<AVCodecID> for Id {
         match self {
             Id::AV1 => &AVCodecID_AV_CODEC_ID_AV1,
             Id::H264 => &AVCodecID_AV_CODEC_ID_H264,
+            Id::AAC => &AVCodecID_AV_CODEC_ID_AAC,
+            Id::FLAC => &AVCodecID_AV_CODEC_ID_FLAC,
+            Id::OPUS => &AVCodecID_AV_CODEC_ID_OPUS,
+            Id::Other(id) => id,
+        }
+    }
+}
+
+impl From<AVCodecID> for Id {
+    fn from(value: AVCodecID) -> Self {
+        match value {
+            AVCodecID_AV_CODEC_ID_AV1 => Id::AV1,
+            AVCodecID_AV_CODEC_ID_H264 => Id::H264,
+            AVCodecID_AV_CODEC_ID_AAC => Id::AAC,
+            AVCodecID_AV_CODEC_ID_FLAC => Id::FLAC,
+            AVCodecID_AV_CODEC_ID_OPUS => Id::OPUS,
+            id => Id::Other(id),
         }
     }
 }
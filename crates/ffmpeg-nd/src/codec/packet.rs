@@ -1,9 +1,8 @@
-use crate::util::Rational;
+use crate::util::{AV_NOPTS_VALUE, Rational};
 use core::ops::{Deref, DerefMut};
 use core::slice;
 use ffi::*;
 
-#[derive(Clone)]
 pub struct Packet {
     ptr: *mut AVPacket,
 }
@@ -39,17 +38,47 @@ impl Packet {
         }
     }
 
+    #[inline(always)]
+    pub fn stream_index(&self) -> u8 {
+        self.stream_index.try_into().unwrap()
+    }
+
     #[inline(always)]
     pub fn set_stream_index(&mut self, index: u8) {
         self.stream_index = index.into()
     }
 
+    /// Whether the packet starts a keyframe, where decoding can begin.
+    pub fn is_key(&self) -> bool {
+        self.flags & AV_PKT_FLAG_KEY != 0
+    }
+
+    /// DTS, falling back to PTS when the DTS is unknown.
+    pub fn dts_or_pts(&self) -> i64 {
+        match self.dts {
+            AV_NOPTS_VALUE => self.pts,
+            dts => dts,
+        }
+    }
+
     #[inline(always)]
     pub fn rescale_ts(&mut self, tb_src: &Rational, tb_dst: &Rational) {
         unsafe { av_packet_rescale_ts(self.ptr, tb_src.into(), tb_dst.into()) };
     }
 }
 
+/// Creates a new reference to the same data, rather than sharing the `AVPacket` itself.
+impl Clone for Packet {
+    fn clone(&self) -> Self {
+        Packet {
+            ptr: unsafe { av_packet_clone(self.ptr) },
+        }
+    }
+}
+
+/// `AVPacket` has no thread affinity; the packet is owned by exactly one `Packet`.
+unsafe impl Send for Packet {}
+
 impl Deref for Packet {
     type Target = AVPacket;
 
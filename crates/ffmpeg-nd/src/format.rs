@@ -1,8 +1,12 @@
 mod context;
+mod disposition;
 mod format;
 mod io;
 mod stream;
 
 pub use context::Context;
+pub use disposition::Disposition;
 pub use format::*;
+pub use io::Interrupt;
+pub(crate) use io::{Context as IOContext, Flag};
 pub use stream::Stream;
@@ -1,13 +1,21 @@
-use super::format::{Format, Output};
-use super::io::{Context as IOContext, Flag};
+use super::format::{Format, Input, Output};
+use super::io::{Context as IOContext, Flag, Interrupt};
+use super::stream::Stream;
 use crate::codec::Packet;
-use crate::util::Error;
+use crate::util::{AV_NOPTS_VALUE, Dictionary, Error, MediaType};
+use core::ffi::{c_char, c_int};
 use ffi::*;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
+use std::io::{Read, Seek, Write};
+use std::time::Duration;
 
 pub struct Context {
     ptr: *mut AVFormatContext,
     _pb: Option<IOContext>,
+    /// Opened by `avformat_open_input` and must be closed by `avformat_close_input`.
+    input: bool,
+    /// Kept alive for `interrupt_callback`.
+    interrupt: Option<Interrupt>,
 }
 
 impl Context {
@@ -25,6 +33,8 @@ impl Context {
         Context {
             ptr: unsafe { avformat_alloc_context() },
             _pb: None,
+            input: false,
+            interrupt: None,
         }
     }
 
@@ -42,6 +52,81 @@ impl Context {
         }
     }
 
+    /// The muxer of an output context, `None` for input contexts.
+    pub fn output(&self) -> Option<Output> {
+        match unsafe { (*self.ptr).oformat } {
+            ptr if ptr.is_null() => None,
+            ptr => Some(ptr.into()),
+        }
+    }
+
+    /// Where a muxer that opens its own files, such as `hls`, writes its main output.
+    pub fn set_url<U: AsRef<str>>(&mut self, url: U) {
+        let url = CString::new(url.as_ref()).unwrap_or_default();
+        unsafe {
+            av_free((*self.ptr).url as *mut core::ffi::c_void);
+            (*self.ptr).url = av_strdup(url.as_ptr());
+        }
+    }
+
+    /// Abort opening, reading and writing once `interrupt` fires. Set it before opening.
+    pub fn set_interrupt(&mut self, interrupt: Interrupt) {
+        unsafe { (*self.ptr).interrupt_callback = interrupt.as_callback() };
+        self.interrupt = Some(interrupt);
+    }
+
+    /// The interrupt set by `set_interrupt`, e.g. to share it with another context.
+    pub fn interrupt(&self) -> Option<Interrupt> {
+        self.interrupt.clone()
+    }
+
+    /// `Error::Interrupted` if the interrupt set by `set_interrupt` has fired.
+    pub fn check_interrupt(&self) -> Result<(), Error> {
+        match &self.interrupt {
+            Some(interrupt) if interrupt.is_interrupted() => Err(Error::Interrupted),
+            _ => Ok(()),
+        }
+    }
+
+    /// Bytes written to the output so far, including those still buffered.
+    pub fn bytes_written(&self) -> u64 {
+        let pb = unsafe { (*self.ptr).pb };
+        if pb.is_null() {
+            return 0;
+        }
+        // `avio_tell`, which is inline and so missing from the bindings.
+        unsafe { avio_seek(pb, 0, 1) }.max(0) as u64
+    }
+
+    /// Upper bound on the time `av_interleaved_write_frame` buffers packets waiting for other streams.
+    /// Zero waits until every stream has a packet.
+    pub fn set_max_interleave_delta(&mut self, value: Duration) {
+        unsafe { (*self.ptr).max_interleave_delta = value.as_micros().try_into().unwrap() };
+    }
+
+    /// SDP describing the outputs in `contexts`, e.g. one `rtp` output per stream. Call it
+    /// after `write_header`, which picks payload types and SSRCs.
+    pub fn sdp(contexts: &mut [Context]) -> Result<String, Error> {
+        let mut ptrs: Vec<*mut AVFormatContext> = contexts
+            .iter_mut()
+            .map(|context| context.as_mut_ptr())
+            .collect();
+        let mut buf = vec![0 as c_char; 16384];
+        match unsafe {
+            av_sdp_create(
+                ptrs.as_mut_ptr(),
+                ptrs.len() as c_int,
+                buf.as_mut_ptr(),
+                buf.len() as c_int,
+            )
+        } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(unsafe { CStr::from_ptr(buf.as_ptr()) }
+                .to_string_lossy()
+                .into_owned()),
+        }
+    }
+
     /// Set private byte IO buffer
     pub fn set_pb(&mut self, mut pb: IOContext) {
         unsafe { (*self.ptr).pb = pb.as_mut_ptr() }
@@ -49,27 +134,245 @@ impl Context {
     }
 
     pub fn open_read<U: AsRef<str>>(&mut self, url: U) -> Result<(), Error> {
-        let mut pb = IOContext::from(unsafe { (*self.ptr).pb });
-        pb.open(url, Flag::Read)?;
-        self.set_pb(pb);
-        Ok(())
+        self.open_read_with_options(url, Dictionary::new())
+            .map(drop)
+    }
+
+    /// Open `url` with protocol options. Returns the options the protocol did not use.
+    pub fn open_read_with_options<U: AsRef<str>>(
+        &mut self,
+        url: U,
+        options: Dictionary,
+    ) -> Result<Dictionary, Error> {
+        self.open_pb(url, Flag::Read, options)
     }
 
     pub fn open_write<U: AsRef<str>>(&mut self, url: U) -> Result<(), Error> {
+        self.open_write_with_options(url, Dictionary::new())
+            .map(drop)
+    }
+
+    /// Open `url` with protocol options. Returns the options the protocol did not use.
+    pub fn open_write_with_options<U: AsRef<str>>(
+        &mut self,
+        url: U,
+        options: Dictionary,
+    ) -> Result<Dictionary, Error> {
+        self.open_pb(url, Flag::Write, options)
+    }
+
+    fn open_pb<U: AsRef<str>>(
+        &mut self,
+        url: U,
+        flag: Flag,
+        options: Dictionary,
+    ) -> Result<Dictionary, Error> {
         let mut pb = IOContext::from(unsafe { (*self.ptr).pb });
-        pb.open(url, Flag::Write)?;
+        let options = pb.open(
+            url,
+            flag,
+            unsafe { &(*self.ptr).interrupt_callback },
+            options,
+        )?;
         self.set_pb(pb);
+        Ok(options)
+    }
+
+    /// Write the output to a Rust stream that cannot seek, e.g. a pipe.
+    pub fn open_writer<W: Write + 'static>(&mut self, writer: W) -> Result<(), Error> {
+        self.set_custom_pb(IOContext::from_writer(writer)?);
         Ok(())
     }
 
-    pub fn write_header(&mut self) -> Result<(), Error> {
-        match unsafe { avformat_write_header(self.ptr, core::ptr::null_mut()) } {
+    /// Write the output to a seekable Rust stream, e.g. a `File` or a `Cursor<Vec<u8>>`.
+    pub fn open_seekable_writer<W: Write + Seek + 'static>(
+        &mut self,
+        writer: W,
+    ) -> Result<(), Error> {
+        self.set_custom_pb(IOContext::from_seekable_writer(writer)?);
+        Ok(())
+    }
+
+    /// Read the input from a Rust stream that cannot seek. The format is probed when `input` is `None`.
+    pub fn open_reader<R: Read + 'static>(
+        &mut self,
+        reader: R,
+        input: Option<Input>,
+    ) -> Result<(), Error> {
+        self.set_custom_pb(IOContext::from_reader(reader)?);
+        self.open_custom_input(input)
+    }
+
+    /// Read the input from a seekable Rust stream. The format is probed when `input` is `None`.
+    pub fn open_seekable_reader<R: Read + Seek + 'static>(
+        &mut self,
+        reader: R,
+        input: Option<Input>,
+    ) -> Result<(), Error> {
+        self.set_custom_pb(IOContext::from_seekable_reader(reader)?);
+        self.open_custom_input(input)
+    }
+
+    fn open_custom_input(&mut self, input: Option<Input>) -> Result<(), Error> {
+        let input = match input {
+            Some(input) => input,
+            None => self.probe_input()?,
+        };
+        self.open_input("", Some(input))
+    }
+
+    /// Guess the format of a custom input from its first bytes.
+    pub fn probe_input(&mut self) -> Result<Input, Error> {
+        let mut fmt = core::ptr::null();
+        match unsafe {
+            av_probe_input_buffer(
+                (*self.ptr).pb,
+                &mut fmt,
+                c"".as_ptr(),
+                core::ptr::null_mut(),
+                0,
+                0,
+            )
+        } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(Input::from(fmt)),
+        }
+    }
+
+    fn set_custom_pb(&mut self, pb: IOContext) {
+        unsafe { (*self.ptr).flags |= AVFMT_FLAG_CUSTOM_IO };
+        self.set_pb(pb);
+    }
+
+    /// Take back the stream passed to `open_writer` or `open_seekable_writer`,
+    /// once the trailer has been written.
+    pub fn take_writer<W: 'static>(&mut self) -> Option<W> {
+        let pb = self._pb.take()?;
+        unsafe { (*self.ptr).pb = core::ptr::null_mut() };
+        pb.into_inner()
+    }
+
+    /// Open an input and read its header. The format is probed when `input` is `None`.
+    pub fn open_input<U: AsRef<str>>(&mut self, url: U, input: Option<Input>) -> Result<(), Error> {
+        let url = CString::new(url.as_ref()).unwrap_or_default();
+        let fmt = input.map_or(core::ptr::null(), |input| input.as_ptr());
+        match unsafe {
+            avformat_open_input(&mut self.ptr, url.as_ptr(), fmt, core::ptr::null_mut())
+        } {
+            e if e < 0 => Err(e.into()),
+            _ => {
+                self.input = true;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn find_stream_info(&mut self) -> Result<(), Error> {
+        match unsafe { avformat_find_stream_info(self.ptr, core::ptr::null_mut()) } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Read the next packet of any stream. Returns an EOF error at the end of the input.
+    pub fn read_frame(&mut self, pkt: &mut Packet) -> Result<(), Error> {
+        self.check_interrupt()?;
+        match unsafe { av_read_frame(self.ptr, pkt.as_mut_ptr()) } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn nb_streams(&self) -> usize {
+        unsafe { (*self.ptr).nb_streams as usize }
+    }
+
+    pub fn stream(&self, index: usize) -> Option<Stream> {
+        if index < self.nb_streams() {
+            Some(unsafe { *(*self.ptr).streams.add(index) }.into())
+        } else {
+            None
+        }
+    }
+
+    pub fn streams(&self) -> Vec<Stream> {
+        (0..self.nb_streams())
+            .filter_map(|index| self.stream(index))
+            .collect()
+    }
+
+    /// Duration of the input in `AV_TIME_BASE` units, if known.
+    pub fn duration(&self) -> Option<i64> {
+        match unsafe { (*self.ptr).duration } {
+            AV_NOPTS_VALUE => None,
+            duration => Some(duration),
+        }
+    }
+
+    /// Time of the first frame of the input in `AV_TIME_BASE` units, if known.
+    pub fn start_time(&self) -> Option<i64> {
+        match unsafe { (*self.ptr).start_time } {
+            AV_NOPTS_VALUE => None,
+            start_time => Some(start_time),
+        }
+    }
+
+    /// The stream `av_find_best_stream` picks for `media_type`, if any.
+    pub fn find_best_stream(&self, media_type: MediaType) -> Option<Stream> {
+        let index = unsafe {
+            av_find_best_stream(
+                self.ptr,
+                media_type.into(),
+                -1,
+                -1,
+                core::ptr::null_mut(),
+                0,
+            )
+        };
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.stream(index))
+    }
+
+    /// Seek all streams to the keyframe at or before `timestamp`, in `AV_TIME_BASE` units.
+    pub fn seek(&mut self, timestamp: i64) -> Result<(), Error> {
+        match unsafe { avformat_seek_file(self.ptr, -1, i64::MIN, timestamp, timestamp, 0) } {
             e if e < 0 => Err(e.into()),
             _ => Ok(()),
         }
     }
 
+    pub fn write_header(&mut self) -> Result<(), Error> {
+        self.write_header_with_options(Dictionary::new()).map(drop)
+    }
+
+    /// Write the header with muxer options. Returns the options the muxer did not use.
+    pub fn write_header_with_options(
+        &mut self,
+        mut options: Dictionary,
+    ) -> Result<Dictionary, Error> {
+        self.check_interrupt()?;
+        match unsafe { avformat_write_header(self.ptr, options.as_mut_ptr_ptr()) } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(options),
+        }
+    }
+
+    /// Set up the muxer with options, without writing anything yet. Returns the options the
+    /// muxer did not use; `write_header` then writes the header.
+    pub fn init_output_with_options(
+        &mut self,
+        mut options: Dictionary,
+    ) -> Result<Dictionary, Error> {
+        self.check_interrupt()?;
+        match unsafe { avformat_init_output(self.ptr, options.as_mut_ptr_ptr()) } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(options),
+        }
+    }
+
     pub fn write_trailer(&mut self) -> Result<(), Error> {
+        self.check_interrupt()?;
         match unsafe { av_write_trailer(self.ptr) } {
             e if e < 0 => Err(e.into()),
             _ => Ok(()),
@@ -77,6 +380,7 @@ impl Context {
     }
 
     pub fn interleaved_write_frame(&mut self, pkt: &mut Packet) -> Result<(), Error> {
+        self.check_interrupt()?;
         match unsafe { av_interleaved_write_frame(self.ptr, pkt.as_mut_ptr()) } {
             e if e < 0 => Err(e.into()),
             _ => Ok(()),
@@ -97,7 +401,11 @@ impl Context {
 
 impl Drop for Context {
     fn drop(&mut self) {
-        unsafe { avformat_free_context(self.ptr) };
+        if self.input {
+            unsafe { avformat_close_input(&mut self.ptr) };
+        } else {
+            unsafe { avformat_free_context(self.ptr) };
+        }
     }
 }
 
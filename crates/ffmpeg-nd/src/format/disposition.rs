@@ -0,0 +1,31 @@
+use ffi::*;
+use std::os::raw::c_int;
+
+/// Flags describing how a player should treat a stream, e.g. which audio track plays by default.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Disposition {
+    Default,
+    Dub,
+    Original,
+    Comment,
+    Forced,
+    HearingImpaired,
+    VisualImpaired,
+    /// The stream is a single cover image.
+    AttachedPic,
+}
+
+impl Into<c_int> for Disposition {
+    fn into(self) -> c_int {
+        match self {
+            Disposition::Default => AV_DISPOSITION_DEFAULT,
+            Disposition::Dub => AV_DISPOSITION_DUB,
+            Disposition::Original => AV_DISPOSITION_ORIGINAL,
+            Disposition::Comment => AV_DISPOSITION_COMMENT,
+            Disposition::Forced => AV_DISPOSITION_FORCED,
+            Disposition::HearingImpaired => AV_DISPOSITION_HEARING_IMPAIRED,
+            Disposition::VisualImpaired => AV_DISPOSITION_VISUAL_IMPAIRED,
+            Disposition::AttachedPic => AV_DISPOSITION_ATTACHED_PIC,
+        }
+    }
+}
@@ -1,7 +1,10 @@
 mod format;
+mod input;
 mod output;
 
-pub(crate) use format::Format;
-pub(crate) use output::Output;
+pub use format::Format;
+pub(crate) use input::Input;
+pub use output::Output;
 
+pub use input::demuxer_iterate;
 pub use output::muxer_iterate;
@@ -1,21 +1,97 @@
-#[derive(Default)]
+use super::output::Output;
+
+/// Container formats with a known muxer and demuxer.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
 pub enum Format {
     WebM,
     #[default]
     Mp4,
+    Matroska,
+    Mov,
+    MpegTs,
+    Ogg,
+    Ivf,
+    /// Raw H.264 Annex B byte stream.
+    H264,
+    Flac,
+    Wav,
+    Mp3,
 }
 
 impl Format {
+    const ALL: [Format; 11] = [
+        Format::WebM,
+        Format::Mp4,
+        Format::Matroska,
+        Format::Mov,
+        Format::MpegTs,
+        Format::Ogg,
+        Format::Ivf,
+        Format::H264,
+        Format::Flac,
+        Format::Wav,
+        Format::Mp3,
+    ];
+
     pub fn short_name(&self) -> &'static str {
         match self {
             Format::WebM => "webm",
             Format::Mp4 => "mp4",
+            Format::Matroska => "matroska",
+            Format::Mov => "mov",
+            Format::MpegTs => "mpegts",
+            Format::Ogg => "ogg",
+            Format::Ivf => "ivf",
+            Format::H264 => "h264",
+            Format::Flac => "flac",
+            Format::Wav => "wav",
+            Format::Mp3 => "mp3",
         }
     }
     pub fn mime_type(&self) -> &'static str {
         match self {
             Format::WebM => "video/webm",
             Format::Mp4 => "video/mp4",
+            Format::Matroska => "video/x-matroska",
+            Format::Mov => "video/quicktime",
+            Format::MpegTs => "video/MP2T",
+            Format::Ogg => "application/ogg",
+            Format::Ivf => "video/x-ivf",
+            Format::H264 => "video/h264",
+            Format::Flac => "audio/x-flac",
+            Format::Wav => "audio/x-wav",
+            Format::Mp3 => "audio/mpeg",
+        }
+    }
+
+    /// Guess the format from the extension of `url`, e.g. `.mkv` or `.ts`.
+    pub fn from_url<U: AsRef<str>>(url: U) -> Option<Self> {
+        let output = Output::from_url(url)?;
+        Self::ALL
+            .into_iter()
+            .find(|format| format.short_name() == output.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        assert_eq!(
+            Format::from_url("file:///tmp/out.mkv"),
+            Some(Format::Matroska)
+        );
+        assert_eq!(Format::from_url("/tmp/out.ts"), Some(Format::MpegTs));
+        assert_eq!(Format::from_url("out.webm"), Some(Format::WebM));
+        assert_eq!(Format::from_url("out.mp4"), Some(Format::Mp4));
+        assert_eq!(Format::from_url("out.wav"), Some(Format::Wav));
+        assert_eq!(Format::from_url("out.unknown"), None);
+
+        for format in Format::ALL {
+            Output::try_from(format).unwrap();
+            super::super::Input::try_from(format).unwrap();
         }
     }
 }
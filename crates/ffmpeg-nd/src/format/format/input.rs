@@ -0,0 +1,74 @@
+mod functions;
+
+use super::format::Format;
+use crate::util::Error;
+use core::ffi::CStr;
+use core::ops::Deref;
+use ffi::*;
+pub use functions::*;
+use std::borrow::Cow;
+
+pub struct Input {
+    ptr: *const AVInputFormat,
+}
+
+impl Input {
+    pub fn as_ptr(&self) -> *const AVInputFormat {
+        self.ptr
+    }
+}
+
+impl Input {
+    pub fn name(&self) -> Cow<'_, str> {
+        unsafe { CStr::from_ptr(self.name).to_string_lossy() }
+    }
+
+    pub fn long_name(&self) -> Cow<'_, str> {
+        unsafe { CStr::from_ptr(self.long_name).to_string_lossy() }
+    }
+
+    pub fn mime_type(&self) -> Option<Cow<'_, str>> {
+        if self.mime_type.is_null() {
+            None
+        } else {
+            Some(unsafe { CStr::from_ptr(self.mime_type).to_string_lossy() })
+        }
+    }
+}
+
+impl Deref for Input {
+    type Target = AVInputFormat;
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.ptr }
+    }
+}
+
+impl From<*const AVInputFormat> for Input {
+    fn from(ptr: *const AVInputFormat) -> Self {
+        Input { ptr }
+    }
+}
+
+impl TryFrom<Format> for Input {
+    type Error = Error;
+
+    fn try_from(format: Format) -> Result<Self, Self::Error> {
+        if let Some(input) = find_input_format(format.short_name()) {
+            Ok(input)
+        } else {
+            Err("Could not find input file format".into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        use super::super::Format;
+        let input = Input::try_from(Format::default()).unwrap();
+        assert!(input.name().split(',').any(|name| name == "mp4"));
+    }
+}
@@ -0,0 +1,45 @@
+use super::Input;
+use ffi::*;
+use std::ffi::CString;
+
+pub fn find_input_format<S: AsRef<str>>(short_name: S) -> Option<Input> {
+    let s = CString::new(short_name.as_ref()).unwrap_or_default();
+    let ptr = unsafe { av_find_input_format(s.as_ptr()) };
+    if ptr.is_null() {
+        None
+    } else {
+        Some(ptr.into())
+    }
+}
+
+pub fn demuxer_iterate() -> impl Iterator<Item = Input> {
+    let mut opaque = core::ptr::null_mut();
+    std::iter::from_fn(move || {
+        let next = unsafe { av_demuxer_iterate(&mut opaque) };
+        if next.is_null() {
+            None
+        } else {
+            Some(next.into())
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demuxer_iterate() {
+        for demuxer in demuxer_iterate() {
+            println!(
+                "{}: {}{}",
+                demuxer.name(),
+                demuxer.long_name(),
+                demuxer
+                    .mime_type()
+                    .map(|s| format!(" ({})", s))
+                    .unwrap_or_default()
+            );
+        }
+    }
+}
@@ -1,7 +1,8 @@
 mod functions;
 
 use super::format::Format;
-use crate::util::Error;
+use crate::codec::{Codec, Id, codec_iterate};
+use crate::util::{Error, MediaType};
 use core::ffi::CStr;
 use core::ops::Deref;
 use ffi::*;
@@ -34,6 +35,53 @@ impl Output {
             Some(unsafe { CStr::from_ptr(self.mime_type).to_string_lossy() })
         }
     }
+
+    /// Whether the muxer can store `id`. `None` if the muxer does not tell.
+    pub fn query_codec(&self, id: &Id) -> Option<bool> {
+        match unsafe { avformat_query_codec(self.ptr, *id.as_ref(), FF_COMPLIANCE_NORMAL) } {
+            e if e < 0 => None,
+            0 => Some(false),
+            _ => Some(true),
+        }
+    }
+
+    /// Encoders whose codec the muxer can store, e.g. to offer only valid choices.
+    pub fn supported_codecs(&self) -> Vec<Codec> {
+        codec_iterate()
+            .filter(|codec| codec.is_encoder())
+            .filter(|codec| self.query_codec(&codec.id.into()) == Some(true))
+            .collect()
+    }
+
+    /// Fail with `Error::UnsupportedCodec` if the muxer is known not to store `id`.
+    pub fn check_codec(&self, id: &Id, media_type: MediaType) -> Result<(), Error> {
+        if self.query_codec(id) != Some(false) {
+            return Ok(());
+        }
+        let mut supported: Vec<String> = codec_iterate()
+            .filter(|codec| codec.type_() == media_type)
+            .map(|codec| Id::from(codec.id))
+            .filter(|id| self.query_codec(id) == Some(true))
+            .map(|id| id.get_name().into_owned())
+            .collect();
+        supported.sort();
+        supported.dedup();
+        Err(Error::UnsupportedCodec {
+            codec: id.get_name().into_owned(),
+            format: self.name().into_owned(),
+            supported,
+        })
+    }
+
+    /// The muxer called `name`, including those `Format` does not list, e.g. `hls`.
+    pub fn from_name<S: AsRef<str>>(name: S) -> Option<Self> {
+        guess_format(name.as_ref(), "", "")
+    }
+
+    /// The muxer matching the extension of `url`, e.g. `matroska` for `.mkv`.
+    pub fn from_url<U: AsRef<str>>(url: U) -> Option<Self> {
+        guess_format("", url.as_ref(), "")
+    }
 }
 
 impl Deref for Output {
@@ -45,7 +93,7 @@ impl Deref for Output {
 
 impl Default for Output {
     fn default() -> Self {
-        Format::WebM.try_into().unwrap()
+        Format::default().try_into().unwrap()
     }
 }
 
@@ -75,5 +123,28 @@ mod tests {
     fn test() {
         use super::super::Format;
         let _output = Output::try_from(Format::default()).unwrap();
+        assert_eq!(
+            Output::from_url("file:///tmp/out.mkv").unwrap().name(),
+            "matroska"
+        );
+        assert!(Output::from_url("out").is_none());
+        assert_eq!(Output::from_name("hls").unwrap().name(), "hls");
+
+        let webm = Output::try_from(Format::WebM).unwrap();
+        assert_eq!(webm.query_codec(&Id::AV1), Some(true));
+        assert_eq!(webm.query_codec(&Id::H264), Some(false));
+        assert!(
+            webm.supported_codecs()
+                .iter()
+                .all(|codec| codec.name() != "libx264")
+        );
+        assert!(webm.check_codec(&Id::AV1, MediaType::Video).is_ok());
+        match webm.check_codec(&Id::H264, MediaType::Video) {
+            Err(Error::UnsupportedCodec { supported, .. }) => {
+                assert!(supported.iter().any(|name| name == "vp9"));
+                assert!(supported.iter().all(|name| name != "opus"));
+            }
+            _ => panic!("H.264 accepted in WebM"),
+        }
     }
 }
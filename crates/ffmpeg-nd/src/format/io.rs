@@ -1,5 +1,8 @@
 mod context;
 mod flag;
+mod interrupt;
+mod opaque;
 
 pub(crate) use context::Context;
 pub(crate) use flag::Flag;
+pub use interrupt::Interrupt;
@@ -1,37 +1,148 @@
 use super::flag::Flag;
-use crate::util::Error;
+use super::opaque::{self, Opaque};
+use crate::util::{Dictionary, Error};
 use ffi::*;
 use std::ffi::CString;
+use std::io::{Read, Seek, Write};
+use std::os::raw::{c_int, c_void};
 use url::Url;
 
+const BUFFER_SIZE: usize = 4096;
+
+type ReadPacket = unsafe extern "C" fn(*mut c_void, *mut u8, c_int) -> c_int;
+type WritePacket = unsafe extern "C" fn(*mut c_void, *const u8, c_int) -> c_int;
+type SeekFn = unsafe extern "C" fn(*mut c_void, i64, c_int) -> i64;
+
 pub struct Context {
     ptr: *mut AVIOContext,
+    /// The Rust stream of a custom context. `None` for URLs opened by `avio_open2`.
+    opaque: Option<Opaque>,
 }
 
 impl Context {
-    // TODO:
-    pub fn new() -> Self {
-        use std::ptr::null_mut;
-        Context {
-            ptr: unsafe { avio_alloc_context(null_mut(), 4096, 0, null_mut(), None, None, None) },
+    pub fn as_mut_ptr(&mut self) -> *mut AVIOContext {
+        self.ptr
+    }
+
+    /// Whether this context reads or writes a Rust stream rather than a URL.
+    pub fn is_custom(&self) -> bool {
+        self.opaque.is_some()
+    }
+}
+
+impl Context {
+    /// Read from a stream that cannot seek, e.g. a request body.
+    pub fn from_reader<R: Read + 'static>(reader: R) -> Result<Self, Error> {
+        Self::alloc(Opaque::new(reader), Some(opaque::read_packet::<R>), None, None)
+    }
+
+    /// Read from a seekable stream, e.g. a `File` or a `Cursor<Vec<u8>>`.
+    pub fn from_seekable_reader<R: Read + Seek + 'static>(reader: R) -> Result<Self, Error> {
+        Self::alloc(
+            Opaque::new(reader),
+            Some(opaque::read_packet::<R>),
+            None,
+            Some(opaque::seek::<R>),
+        )
+    }
+
+    /// Write to a stream that cannot seek, e.g. a pipe.
+    pub fn from_writer<W: Write + 'static>(writer: W) -> Result<Self, Error> {
+        Self::alloc(Opaque::new(writer), None, Some(opaque::write_packet::<W>), None)
+    }
+
+    /// Write to a seekable stream, e.g. a `File` or a `Cursor<Vec<u8>>`, so that muxers
+    /// can go back and patch headers such as the MP4 `moov` atom.
+    pub fn from_seekable_writer<W: Write + Seek + 'static>(writer: W) -> Result<Self, Error> {
+        Self::alloc(
+            Opaque::new(writer),
+            None,
+            Some(opaque::write_packet::<W>),
+            Some(opaque::seek::<W>),
+        )
+    }
+
+    fn alloc(
+        mut opaque: Opaque,
+        read_packet: Option<ReadPacket>,
+        write_packet: Option<WritePacket>,
+        seek: Option<SeekFn>,
+    ) -> Result<Self, Error> {
+        // The buffer belongs to the context from here on; FFmpeg may replace it.
+        let buffer = unsafe { av_malloc(BUFFER_SIZE) } as *mut u8;
+        if buffer.is_null() {
+            return Err("Could not allocate AVIOContext buffer".into());
+        }
+        let write_flag = write_packet.is_some().into();
+        let ptr = unsafe {
+            avio_alloc_context(
+                buffer,
+                BUFFER_SIZE as c_int,
+                write_flag,
+                opaque.as_mut_ptr(),
+                read_packet,
+                write_packet,
+                seek,
+            )
+        };
+        if ptr.is_null() {
+            unsafe { av_free(buffer as *mut c_void) };
+            return Err("Could not allocate AVIOContext".into());
         }
+        Ok(Context {
+            ptr,
+            opaque: Some(opaque),
+        })
     }
 
-    pub fn as_mut_ptr(&mut self) -> *mut AVIOContext {
-        self.ptr
+    /// Flush a custom context and hand back its stream, if it is a `T`.
+    pub fn into_inner<T: 'static>(mut self) -> Option<T> {
+        self.free_custom();
+        self.opaque.take()?.into_inner()
+    }
+
+    fn free_custom(&mut self) {
+        if self.ptr.is_null() {
+            return;
+        }
+        unsafe {
+            if (*self.ptr).write_flag != 0 {
+                avio_flush(self.ptr);
+            }
+            av_freep(&mut (*self.ptr).buffer as *mut *mut u8 as *mut c_void);
+            avio_context_free(&mut self.ptr);
+        }
     }
 }
 
 impl Context {
-    pub fn open<U: AsRef<str>>(&mut self, url: U, flag: Flag) -> Result<(), Error> {
+    /// `int_cb` may be null, or the interrupt callback of the format context, see `Interrupt`.
+    /// Returns the protocol options that were not used.
+    pub fn open<U: AsRef<str>>(
+        &mut self,
+        url: U,
+        flag: Flag,
+        int_cb: *const AVIOInterruptCB,
+        mut options: Dictionary,
+    ) -> Result<Dictionary, Error> {
         let url = Url::try_from(url.as_ref()).map_err(|e| format!("ParseError: {:?}", e))?;
         let url = CString::new(url.as_ref()).unwrap_or_default();
 
-        // TODO: Implement
-        let int_cb = core::ptr::null_mut();
-        let options: *mut *mut AVDictionary = core::ptr::null_mut();
+        match unsafe {
+            avio_open2(&mut self.ptr, url.as_ptr(), flag.into(), int_cb, options.as_mut_ptr_ptr())
+        } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(options),
+        }
+    }
 
-        match unsafe { avio_open2(&mut self.ptr, url.as_ptr(), flag.into(), int_cb, options) } {
+    /// Write `data` and flush it to the protocol at once, e.g. as one network write.
+    pub fn write_and_flush(&mut self, data: &[u8]) -> Result<(), Error> {
+        unsafe {
+            avio_write(self.ptr, data.as_ptr(), data.len() as c_int);
+            avio_flush(self.ptr);
+        }
+        match unsafe { (*self.ptr).error } {
             e if e < 0 => Err(e.into()),
             _ => Ok(()),
         }
@@ -40,14 +151,19 @@ impl Context {
 
 impl From<*mut AVIOContext> for Context {
     fn from(ptr: *mut AVIOContext) -> Self {
-        Context { ptr }
+        Context { ptr, opaque: None }
     }
 }
 
 impl Drop for Context {
     fn drop(&mut self) {
+        if self.is_custom() {
+            return self.free_custom();
+        }
+        // A failed write was already reported; closing then fails the same way.
+        let failed = !self.ptr.is_null() && unsafe { (*self.ptr).error } < 0;
         match unsafe { avio_closep(&mut self.ptr) } {
-            e if e < 0 => panic!("Failed to close AVIOContext: {}", e),
+            e if e < 0 && !failed => panic!("Failed to close AVIOContext: {}", e),
             _ => (),
         }
         unsafe { avio_context_free(&mut self.ptr) }
@@ -76,6 +192,39 @@ mod tests {
         });
 
         let mut context = Context::from(core::ptr::null_mut());
-        context.open("tcp://127.0.0.1:8080", Flag::Write).unwrap();
+        context
+            .open("tcp://127.0.0.1:8080", Flag::Write, core::ptr::null(), Dictionary::new())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_writer() {
+        use std::io::Cursor;
+
+        let mut context = Context::from_seekable_writer(Cursor::new(Vec::new())).unwrap();
+        unsafe {
+            avio_write(context.as_mut_ptr(), b"abcdef".as_ptr(), 6);
+            avio_seek(context.as_mut_ptr(), 1, 0);
+            avio_write(context.as_mut_ptr(), b"X".as_ptr(), 1);
+            assert_eq!(avio_size(context.as_mut_ptr()), 6);
+        }
+        assert!(context.is_custom());
+
+        let cursor: Cursor<Vec<u8>> = context.into_inner().unwrap();
+        assert_eq!(cursor.into_inner(), b"aXcdef");
+
+        let context = Context::from_writer(Vec::<u8>::new()).unwrap();
+        assert!(context.into_inner::<String>().is_none());
+    }
+
+    #[test]
+    fn test_reader() {
+        let mut context = Context::from_reader(&b"abcdef"[..]).unwrap();
+        let mut buf = [0u8; 8];
+        let read = unsafe { avio_read(context.as_mut_ptr(), buf.as_mut_ptr(), 8) };
+        assert_eq!(read, 6);
+        assert_eq!(&buf[..6], b"abcdef");
+        let read = unsafe { avio_read(context.as_mut_ptr(), buf.as_mut_ptr(), 8) };
+        assert_eq!(read, crate::util::AVERROR_EOF);
     }
 }
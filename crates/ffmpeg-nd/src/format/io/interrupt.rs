@@ -0,0 +1,85 @@
+use ffi::*;
+use std::os::raw::{c_int, c_void};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Cancellation token and deadline checked by FFmpeg while it blocks on I/O.
+/// Clones share state, so another thread can `cancel` a mux or demux in progress.
+#[derive(Clone, Default)]
+pub struct Interrupt(Arc<State>);
+
+#[derive(Default)]
+struct State {
+    cancelled: AtomicBool,
+    deadline: Mutex<Option<Instant>>,
+}
+
+impl State {
+    fn is_interrupted(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .lock()
+                .unwrap()
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+impl Interrupt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Interrupt once `timeout` has passed from now.
+    pub fn set_timeout(&self, timeout: Duration) {
+        self.set_deadline(Some(Instant::now() + timeout));
+    }
+
+    pub fn set_deadline(&self, deadline: Option<Instant>) {
+        *self.0.deadline.lock().unwrap() = deadline;
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.0.is_interrupted()
+    }
+
+    /// Valid for as long as this `Interrupt` or a clone of it is alive.
+    pub(crate) fn as_callback(&self) -> AVIOInterruptCB {
+        AVIOInterruptCB {
+            callback: Some(callback),
+            opaque: Arc::as_ptr(&self.0) as *mut c_void,
+        }
+    }
+}
+
+unsafe extern "C" fn callback(opaque: *mut c_void) -> c_int {
+    let state = unsafe { &*(opaque as *const State) };
+    state.is_interrupted().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let interrupt = Interrupt::new();
+        let cb = interrupt.as_callback();
+        let check = || unsafe { cb.callback.unwrap()(cb.opaque) };
+        assert_eq!(check(), 0);
+
+        interrupt.set_timeout(Duration::ZERO);
+        assert_eq!(check(), 1);
+        interrupt.set_deadline(None);
+        assert_eq!(check(), 0);
+
+        interrupt.clone().cancel();
+        assert!(interrupt.is_interrupted());
+        assert_eq!(check(), 1);
+    }
+}
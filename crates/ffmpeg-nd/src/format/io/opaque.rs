@@ -0,0 +1,107 @@
+//! Rust streams behind custom `AVIOContext`s, and the callbacks FFmpeg calls on them.
+
+use crate::util::{AVERROR_EIO, AVERROR_EOF};
+use core::mem::ManuallyDrop;
+use core::slice;
+use ffi::*;
+use std::any::TypeId;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::raw::{c_int, c_void};
+
+/// A boxed Rust stream passed to FFmpeg as the `opaque` pointer.
+pub(super) struct Opaque {
+    ptr: *mut c_void,
+    type_id: TypeId,
+    drop: unsafe fn(*mut c_void),
+}
+
+impl Opaque {
+    pub fn new<T: 'static>(stream: T) -> Self {
+        Opaque {
+            ptr: Box::into_raw(Box::new(stream)) as *mut c_void,
+            type_id: TypeId::of::<T>(),
+            drop: drop_box::<T>,
+        }
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut c_void {
+        self.ptr
+    }
+
+    /// The stream back, if it is a `T`.
+    pub fn into_inner<T: 'static>(self) -> Option<T> {
+        if self.type_id != TypeId::of::<T>() {
+            return None;
+        }
+        let opaque = ManuallyDrop::new(self);
+        Some(*unsafe { Box::from_raw(opaque.ptr as *mut T) })
+    }
+}
+
+impl Drop for Opaque {
+    fn drop(&mut self) {
+        unsafe { (self.drop)(self.ptr) }
+    }
+}
+
+unsafe fn drop_box<T>(ptr: *mut c_void) {
+    drop(unsafe { Box::from_raw(ptr as *mut T) })
+}
+
+fn averror(e: &std::io::Error) -> c_int {
+    e.raw_os_error().map_or(AVERROR_EIO, |errno| -errno)
+}
+
+/// Returns `AVERROR_EOF` rather than 0 at the end of the stream, as FFmpeg expects.
+pub(super) unsafe extern "C" fn read_packet<R: Read>(
+    opaque: *mut c_void,
+    buf: *mut u8,
+    buf_size: c_int,
+) -> c_int {
+    let reader = unsafe { &mut *(opaque as *mut R) };
+    let buf = unsafe { slice::from_raw_parts_mut(buf, buf_size as usize) };
+    loop {
+        match reader.read(buf) {
+            Ok(0) => return AVERROR_EOF,
+            Ok(n) => return n as c_int,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return averror(&e),
+        }
+    }
+}
+
+pub(super) unsafe extern "C" fn write_packet<W: Write>(
+    opaque: *mut c_void,
+    buf: *const u8,
+    buf_size: c_int,
+) -> c_int {
+    let writer = unsafe { &mut *(opaque as *mut W) };
+    let buf = unsafe { slice::from_raw_parts(buf, buf_size as usize) };
+    match writer.write_all(buf) {
+        Ok(()) => buf_size,
+        Err(e) => averror(&e),
+    }
+}
+
+/// `whence` is one of `SEEK_SET`, `SEEK_CUR` and `SEEK_END`, or `AVSEEK_SIZE` to ask for the total size.
+pub(super) unsafe extern "C" fn seek<S: Seek>(opaque: *mut c_void, offset: i64, whence: c_int) -> i64 {
+    let stream = unsafe { &mut *(opaque as *mut S) };
+    let result = match whence & !AVSEEK_FORCE {
+        AVSEEK_SIZE => stream_len(stream),
+        0 => stream.seek(SeekFrom::Start(offset as u64)),
+        1 => stream.seek(SeekFrom::Current(offset)),
+        2 => stream.seek(SeekFrom::End(offset)),
+        _ => return -1,
+    };
+    match result {
+        Ok(position) => position as i64,
+        Err(e) => averror(&e).into(),
+    }
+}
+
+fn stream_len<S: Seek>(stream: &mut S) -> std::io::Result<u64> {
+    let position = stream.stream_position()?;
+    let len = stream.seek(SeekFrom::End(0))?;
+    stream.seek(SeekFrom::Start(position))?;
+    Ok(len)
+}
@@ -1,7 +1,11 @@
 use super::super::codec::Context as CodecContext;
 use super::context::Context;
-use crate::util::{Error, Rational};
+use super::disposition::Disposition;
+use crate::util::{AV_NOPTS_VALUE, Dictionary, Error, MediaType, Rational};
 use ffi::*;
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::os::raw::c_int;
 use std::ops::{Deref, DerefMut};
 
 pub struct Stream {
@@ -22,6 +26,53 @@ impl Stream {
         }
     }
 
+    pub fn parameters_to_context(&self, codec: &mut CodecContext) -> Result<(), Error> {
+        match unsafe { avcodec_parameters_to_context(codec.as_mut_ptr(), self.codecpar) } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Take the codec parameters of a stream from another container, for stream copy.
+    pub fn parameters_from_stream(&mut self, src: &Stream) -> Result<(), Error> {
+        match unsafe { avcodec_parameters_copy(self.codecpar, src.codecpar) } {
+            e if e < 0 => Err(e.into()),
+            _ => {
+                // The tag is container specific; let the muxer pick its own.
+                unsafe { (*self.codecpar).codec_tag = 0 };
+                Ok(())
+            }
+        }
+    }
+
+    pub fn codec_id(&self) -> AVCodecID {
+        unsafe { (*self.codecpar).codec_id }
+    }
+
+    pub fn codec_name(&self) -> Cow<'static, str> {
+        unsafe { CStr::from_ptr(avcodec_get_name(self.codec_id())).to_string_lossy() }
+    }
+
+    pub fn codec_type(&self) -> MediaType {
+        unsafe { (*self.codecpar).codec_type }.into()
+    }
+
+    pub fn width(&self) -> u16 {
+        unsafe { (*self.codecpar).width }.try_into().unwrap()
+    }
+
+    pub fn height(&self) -> u16 {
+        unsafe { (*self.codecpar).height }.try_into().unwrap()
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        unsafe { (*self.codecpar).sample_rate }.try_into().unwrap()
+    }
+
+    pub fn bit_rate(&self) -> i64 {
+        unsafe { (*self.codecpar).bit_rate }
+    }
+
     pub fn index(&self) -> u8 {
         self.index.try_into().unwrap()
     }
@@ -29,6 +80,55 @@ impl Stream {
     pub fn time_base(&self) -> Rational {
         self.time_base.into()
     }
+
+    /// A hint for the muxer, which may pick another time base in `write_header`.
+    pub fn set_time_base<R: Into<Rational>>(&mut self, value: R) {
+        self.time_base = value.into().into();
+    }
+
+    pub fn metadata(&self) -> Dictionary {
+        (self.metadata as *const AVDictionary).into()
+    }
+
+    /// Merge entries such as `language` or `title` into the stream metadata.
+    pub fn set_metadata(&mut self, metadata: &Dictionary) -> Result<(), Error> {
+        match unsafe { av_dict_copy(&mut self.metadata, metadata.as_ptr(), 0) } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn has_disposition(&self, disposition: Disposition) -> bool {
+        self.disposition & Into::<c_int>::into(disposition) != 0
+    }
+
+    pub fn set_disposition(&mut self, dispositions: &[Disposition]) {
+        self.disposition = dispositions
+            .iter()
+            .fold(0, |flags, &disposition| flags | Into::<c_int>::into(disposition));
+    }
+
+    /// Timestamp of the first frame in `time_base` units, if known.
+    pub fn start_time(&self) -> Option<i64> {
+        match self.start_time {
+            AV_NOPTS_VALUE => None,
+            start_time => Some(start_time),
+        }
+    }
+
+    /// Duration in `time_base` units, if known.
+    pub fn duration(&self) -> Option<i64> {
+        match self.duration {
+            AV_NOPTS_VALUE => None,
+            duration => Some(duration),
+        }
+    }
+}
+
+impl From<*mut AVStream> for Stream {
+    fn from(ptr: *mut AVStream) -> Self {
+        Stream { ptr }
+    }
 }
 
 impl<'a> Deref for Stream {
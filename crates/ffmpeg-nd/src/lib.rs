@@ -3,8 +3,27 @@ extern crate ffmpeg_nd_sys as ffi;
 mod codec;
 mod format;
 mod nd_media;
+#[cfg(feature = "swresample")]
+mod swresample;
+#[cfg(feature = "swscale")]
+mod swscale;
 mod util;
 
-pub use format::muxer_iterate;
-pub use nd_media::{Encoder, Muxer};
-pub use util::{Level as LogLevel, version_info};
+pub use codec::Codec;
+pub use format::{Disposition, Format, Interrupt, Output, Stream, demuxer_iterate, muxer_iterate};
+#[cfg(all(feature = "swscale", feature = "swresample"))]
+pub use nd_media::Transcoder;
+pub use nd_media::{
+    AudioConfig, Decoder, Demuxer, Encoder, FailurePolicy, Fragment, FragmentOptions, ImageSender,
+    InputFormat, InputStream, LiveOptions, Muxer, MuxerBuilder, Packaging, PackagingOptions,
+    PlaylistType, Resize, Segment, SegmentFormat, SegmentOptions, StreamHandle, StreamOptions,
+    StreamPackets, VideoConfig, remux, remux_as, save_thumbnail, thumbnail,
+};
+#[cfg(feature = "swresample")]
+pub use swresample::Resampler;
+#[cfg(feature = "swscale")]
+pub use swscale::{Interpolation, Scaler};
+pub use util::{
+    ChannelLayout, Dictionary, Error, Frame, Level as LogLevel, PixelFormat, SampleFormat,
+    version_info,
+};
@@ -1,11 +1,36 @@
+mod audio_context_builder;
+mod decoder;
+mod demuxer;
 mod encoder;
 mod frame_data;
+mod frame_iterator;
 mod functions;
+mod image_sender;
 mod media_context;
 mod muxer;
 mod packet_iterator;
+mod remux;
+mod sample_buffer;
+#[cfg(test)]
+mod test_util;
+mod thumbnail;
+#[cfg(all(feature = "swscale", feature = "swresample"))]
+mod transcoder;
 mod video_context_builder;
 
-pub use encoder::Encoder;
-pub use muxer::Muxer;
+pub use decoder::Decoder;
+pub use demuxer::{Demuxer, InputStream, StreamPackets};
+pub use encoder::{AudioConfig, Encoder, VideoConfig};
+pub use frame_data::InputFormat;
+pub use image_sender::{ImageSender, Resize};
+pub use muxer::{
+    FailurePolicy, Fragment, FragmentOptions, LiveOptions, Muxer, MuxerBuilder, Packaging,
+    PackagingOptions, PlaylistType, Segment, SegmentFormat, SegmentOptions, StreamHandle,
+    StreamOptions,
+};
+pub use remux::{remux, remux_as};
+pub use thumbnail::{save_thumbnail, thumbnail};
+#[cfg(all(feature = "swscale", feature = "swresample"))]
+pub use transcoder::Transcoder;
+pub(crate) use audio_context_builder::AudioContextBuilder;
 pub(crate) use video_context_builder::VideoContextBuilder;
@@ -0,0 +1,104 @@
+//! Audio counterpart of `VideoContextBuilder`.
+
+use crate::codec::{Codec, Context};
+use crate::util::{ChannelLayout, Dictionary, Error, Rational, SampleFormat};
+
+pub struct AudioContextBuilder {
+    codec: Codec,
+    sample_rate: Option<u32>,
+    sample_fmt: Option<SampleFormat>,
+    ch_layout: Option<ChannelLayout>,
+    time_base: Option<Rational>,
+    bit_rate: Option<u32>,
+    options: Dictionary,
+}
+
+impl AudioContextBuilder {
+    pub fn new(codec: Codec) -> Self {
+        AudioContextBuilder {
+            codec,
+            sample_rate: None,
+            sample_fmt: None,
+            ch_layout: None,
+            time_base: None,
+            bit_rate: None,
+            options: Dictionary::new(),
+        }
+    }
+
+    pub fn sample_rate(mut self, value: u32) -> Self {
+        self.sample_rate = Some(value);
+        self
+    }
+
+    pub fn sample_fmt<S: Into<SampleFormat>>(mut self, value: S) -> Self {
+        self.sample_fmt = Some(value.into());
+        self
+    }
+
+    pub fn ch_layout<C: Into<ChannelLayout>>(mut self, value: C) -> Self {
+        self.ch_layout = Some(value.into());
+        self
+    }
+
+    /// Defaults to `1 / sample_rate` so that pts count samples.
+    pub fn time_base<R: Into<Rational>>(mut self, value: R) -> Self {
+        self.time_base = Some(value.into());
+        self
+    }
+
+    pub fn bit_rate(mut self, value: u32) -> Self {
+        self.bit_rate = Some(value);
+        self
+    }
+
+    /// Codec and private codec options. Unknown keys fail `build`.
+    pub fn options(mut self, value: Dictionary) -> Self {
+        self.options = value;
+        self
+    }
+
+    pub fn build(self) -> Result<Context, Error> {
+        let mut ctx = Context::from(self.codec);
+        if let Some(sample_rate) = self.sample_rate {
+            ctx.set_sample_rate(sample_rate);
+        }
+        if let Some(sample_fmt) = self.sample_fmt {
+            ctx.set_sample_fmt(sample_fmt);
+        }
+        if let Some(ch_layout) = self.ch_layout {
+            ctx.set_ch_layout(&ch_layout)?;
+        }
+        let time_base = self
+            .time_base
+            .unwrap_or_else(|| Rational::new(1, ctx.sample_rate()));
+        ctx.set_time_base(time_base);
+        if let Some(bit_rate) = self.bit_rate {
+            ctx.set_bit_rate(bit_rate);
+        }
+
+        ctx.open_with_options(self.options)?.ensure_consumed()?;
+        Ok(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        use crate::codec::{Name, find_encoder_by_name};
+        let codec = find_encoder_by_name(&Name::Aac).unwrap();
+
+        let context = AudioContextBuilder::new(codec)
+            .sample_rate(48000)
+            .sample_fmt(SampleFormat::FLTP)
+            .ch_layout(ChannelLayout::stereo())
+            .bit_rate(128000)
+            .build()
+            .unwrap();
+        assert_eq!(context.frame_size(), 1024);
+        assert_eq!(context.time_base().den, 48000);
+    }
+}
@@ -0,0 +1,88 @@
+use super::frame_iterator::FrameIterator;
+use crate::codec::{Codec, Context, Id, find_decoder};
+use crate::format::Stream;
+use crate::util::{Error, Frame, MediaType};
+use core::ops::{Deref, DerefMut};
+
+pub struct Decoder(pub Context);
+
+impl Deref for Decoder {
+    type Target = Context;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Decoder {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl TryFrom<Context> for Decoder {
+    type Error = Error;
+
+    fn try_from(context: Context) -> Result<Self, Self::Error> {
+        if context.codec.is_null() || !Codec::from(context.codec).is_decoder() {
+            return Err("Codec context is not a decoder".into());
+        }
+        match context.codec_type() {
+            MediaType::Video | MediaType::Audio => Ok(Self(context)),
+            media_type => Err(format!("Unsupported media type {:?}", media_type).into()),
+        }
+    }
+}
+
+impl TryFrom<Codec> for Decoder {
+    type Error = Error;
+
+    fn try_from(codec: Codec) -> Result<Self, Self::Error> {
+        if !codec.is_decoder() {
+            return Err(format!("'{}' is not a decoder", codec.name()).into());
+        }
+        let mut context = Context::from(codec);
+        context.open()?;
+        context.try_into()
+    }
+}
+
+/// Builds a decoder from the codec parameters and time base of a stream.
+impl TryFrom<&Stream> for Decoder {
+    type Error = Error;
+
+    fn try_from(stream: &Stream) -> Result<Self, Self::Error> {
+        let id = Id::from(stream.codec_id());
+        let Some(codec) = find_decoder(&id) else {
+            return Err(format!("Could not find decoder for '{}'", id.get_name()).into());
+        };
+        let mut context = Context::from(codec);
+        stream.parameters_to_context(&mut context)?;
+        context.set_pkt_timebase(stream.time_base());
+        context.open()?;
+        context.try_into()
+    }
+}
+
+impl IntoIterator for Decoder {
+    type Item = Result<Frame, Box<dyn std::error::Error>>;
+    type IntoIter = FrameIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let decoder: Decoder = find_decoder(&Id::AV1).unwrap().try_into().unwrap();
+        let _frame_iter = decoder.into_iter();
+
+        let subtitles = crate::codec::find_decoder_by_name("subrip").unwrap();
+        assert!(Decoder::try_from(subtitles).is_err());
+    }
+}
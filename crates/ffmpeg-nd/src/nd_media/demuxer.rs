@@ -0,0 +1,251 @@
+mod stream_packets;
+
+use crate::codec::Packet;
+use crate::format::{Context, Format, Input, Interrupt, Stream};
+use crate::util::{Error, MediaType};
+use std::io::{Read, Seek};
+use std::time::Duration;
+pub use stream_packets::{InputStream, StreamPackets};
+
+pub struct Demuxer {
+    context: Context,
+    eof: bool,
+}
+
+impl Demuxer {
+    /// Open `url` and probe its container format and streams.
+    pub fn open<U: AsRef<str>>(url: U) -> Result<Self, Error> {
+        Self::open_input(url, None)
+    }
+
+    /// Open `url` as the given container format instead of probing it.
+    pub fn open_as<U: AsRef<str>>(url: U, format: Format) -> Result<Self, Error> {
+        Self::open_input(url, Some(format.try_into()?))
+    }
+
+    /// Like `open`, but opening and reading fail with `Error::Interrupted` once `interrupt` fires.
+    pub fn open_with_interrupt<U: AsRef<str>>(url: U, interrupt: Interrupt) -> Result<Self, Error> {
+        let mut context = Context::new();
+        context.set_interrupt(interrupt);
+        context.open_input(url, None)?;
+        Self::from_context(context)
+    }
+
+    /// Read from a Rust stream that cannot seek, e.g. an upload body, probing its format.
+    pub fn from_reader<R: Read + 'static>(reader: R) -> Result<Self, Error> {
+        let mut context = Context::new();
+        context.open_reader(reader, None)?;
+        Self::from_context(context)
+    }
+
+    /// Read from a seekable Rust stream, e.g. a `Cursor<Vec<u8>>`, probing its format.
+    pub fn from_seekable_reader<R: Read + Seek + 'static>(reader: R) -> Result<Self, Error> {
+        let mut context = Context::new();
+        context.open_seekable_reader(reader, None)?;
+        Self::from_context(context)
+    }
+
+    fn open_input<U: AsRef<str>>(url: U, input: Option<Input>) -> Result<Self, Error> {
+        let mut context = Context::new();
+        context.open_input(url, input)?;
+        Self::from_context(context)
+    }
+
+    fn from_context(mut context: Context) -> Result<Self, Error> {
+        context.find_stream_info()?;
+        Ok(Self {
+            context,
+            eof: false,
+        })
+    }
+
+    pub fn streams(&self) -> Vec<Stream> {
+        self.context.streams()
+    }
+
+    pub fn stream(&self, index: usize) -> Option<Stream> {
+        self.context.stream(index)
+    }
+
+    /// The default stream of the given type, e.g. the main video stream.
+    pub fn best_stream(&self, media_type: MediaType) -> Option<Stream> {
+        self.context.find_best_stream(media_type)
+    }
+
+    /// Duration of the whole input in `AV_TIME_BASE` units, if known.
+    pub fn duration(&self) -> Option<i64> {
+        self.context.duration()
+    }
+
+    /// Time of the first frame in `AV_TIME_BASE` units, if known. Not zero for every input,
+    /// e.g. MPEG-TS timestamps usually begin later.
+    pub fn start_time(&self) -> Option<i64> {
+        self.context.start_time()
+    }
+
+    /// Continue reading from the keyframe at or before `position`. Like `ffmpeg -ss`, the
+    /// position counts from `start_time`.
+    pub fn seek(&mut self, position: Duration) -> Result<(), Error> {
+        let start = self.start_time().unwrap_or(0);
+        let timestamp = i64::try_from(position.as_micros()).ok();
+        let Some(timestamp) = timestamp.and_then(|micros| micros.checked_add(start)) else {
+            return Err(format!("Cannot seek to {:?}", position).into());
+        };
+        self.context.seek(timestamp)?;
+        self.eof = false;
+        Ok(())
+    }
+
+    /// Split into each stream with an iterator over its packets, e.g. to copy each stream into
+    /// a `MuxerBuilder` with `add_copied_stream`. Packets of the other streams are kept until
+    /// their iterator reads them or is dropped.
+    pub fn into_streams(self) -> Vec<(InputStream, StreamPackets)> {
+        StreamPackets::split(self)
+    }
+}
+
+/// Yields the packets of all streams in file order, tagged by `Packet::stream_index`.
+impl Iterator for Demuxer {
+    type Item = Result<Packet, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.eof {
+            return None;
+        }
+
+        let mut packet = Packet::new();
+        match self.context.read_frame(&mut packet) {
+            Ok(()) => Some(Ok(packet)),
+            Err(e) if e.is_eof() => {
+                self.eof = true;
+                None
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nd_media::test_util::{encode, encode_silence};
+
+    #[test]
+    fn test() {
+        use crate::util::MediaType;
+        use crate::{Encoder, Muxer};
+        use tempfile::NamedTempFile;
+
+        let video_pkt_iter = encode(&Encoder::video_config(), 60);
+
+        let named_temp_file = NamedTempFile::new().unwrap();
+        let url = format!("file://{}", named_temp_file.path().display());
+
+        let mut context = Context::default();
+        context.open_write(&url).unwrap();
+        Muxer::try_from((context, vec![video_pkt_iter]))
+            .unwrap()
+            .mux()
+            .unwrap();
+
+        let mut demuxer = Demuxer::open(&url).unwrap();
+        assert!(demuxer.seek(Duration::MAX).is_err());
+        demuxer.seek(Duration::ZERO).unwrap();
+        let streams = demuxer.streams();
+        assert_eq!(streams.len(), 1);
+        assert!(streams[0].codec_type() == MediaType::Video);
+        assert_eq!(streams[0].width(), 320);
+        assert!(streams[0].duration().is_some());
+
+        let packets = demuxer.collect::<Result<Vec<Packet>, _>>().unwrap();
+        assert_eq!(packets.len(), 60);
+        assert!(packets.iter().all(|packet| packet.stream_index() == 0));
+    }
+
+    #[test]
+    fn test_reader() {
+        use crate::{Encoder, Muxer};
+        use std::io::Cursor;
+
+        let video_pkt_iter = encode(&Encoder::video_config(), 30);
+
+        let mut context = Context::try_from(Format::WebM).unwrap();
+        context
+            .open_seekable_writer(Cursor::new(Vec::new()))
+            .unwrap();
+        let mut muxer = Muxer::try_from((context, vec![video_pkt_iter])).unwrap();
+        muxer.mux().unwrap();
+        let bytes = muxer.into_writer::<Cursor<Vec<u8>>>().unwrap().into_inner();
+
+        let demuxer = Demuxer::from_seekable_reader(Cursor::new(bytes.clone())).unwrap();
+        assert_eq!(demuxer.streams().len(), 1);
+        assert_eq!(demuxer.map(Result::unwrap).count(), 30);
+
+        // A plain `Read` is probed without seeking.
+        let demuxer = Demuxer::from_reader(Cursor::new(bytes)).unwrap();
+        assert_eq!(demuxer.map(Result::unwrap).count(), 30);
+    }
+
+    #[test]
+    fn test_into_streams() {
+        use crate::{Encoder, Muxer, StreamOptions};
+        use std::io::Cursor;
+
+        let video_pkt_iter = encode(&Encoder::video_config(), 30);
+        let audio_pkt_iter = encode_silence(50);
+
+        let mut builder = Muxer::builder(Format::Mp4)
+            .unwrap()
+            .open_seekable_writer(Cursor::new(Vec::new()))
+            .unwrap();
+        builder
+            .add_stream(video_pkt_iter, StreamOptions::new())
+            .unwrap();
+        builder
+            .add_stream(audio_pkt_iter, StreamOptions::new())
+            .unwrap();
+        let mut muxer = builder.build();
+        muxer.mux().unwrap();
+        let bytes = muxer.into_writer::<Cursor<Vec<u8>>>().unwrap().into_inner();
+
+        let demuxer = Demuxer::from_seekable_reader(Cursor::new(bytes)).unwrap();
+        let mut streams = demuxer.into_streams();
+        assert_eq!(streams.len(), 2);
+        let (_, audio_packets) = streams.pop().unwrap();
+        let (video_stream, video_packets) = streams.pop().unwrap();
+        assert!(video_stream.codec_type() == MediaType::Video);
+
+        // Reading the audio first buffers the video packets in between.
+        let audio_packets = audio_packets.collect::<Result<Vec<Packet>, _>>().unwrap();
+        assert!(
+            audio_packets
+                .iter()
+                .all(|packet| packet.stream_index() == 1)
+        );
+        let video_packets = video_packets.collect::<Result<Vec<Packet>, _>>().unwrap();
+        assert_eq!(video_packets.len(), 30);
+        assert!(
+            video_packets
+                .iter()
+                .all(|packet| packet.stream_index() == 0)
+        );
+
+        // The stream outlives its packet iterator.
+        assert_eq!(video_stream.width(), 320);
+    }
+
+    #[test]
+    fn test_interrupt() {
+        use tempfile::NamedTempFile;
+
+        let named_temp_file = NamedTempFile::new().unwrap();
+        let url = format!("file://{}", named_temp_file.path().display());
+
+        let interrupt = Interrupt::new();
+        interrupt.cancel();
+        match Demuxer::open_with_interrupt(&url, interrupt) {
+            Err(e) => assert!(e.is_interrupted(), "{}", e),
+            Ok(_) => panic!("opened despite the interrupt"),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use super::Demuxer;
+use crate::codec::Packet;
+use crate::format::Stream;
+use crate::util::Error;
+use core::ops::Deref;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+struct Shared {
+    demuxer: Demuxer,
+    /// Packets read ahead for each stream. `None` once the stream's iterator is dropped.
+    queues: Vec<Option<VecDeque<Packet>>>,
+}
+
+/// One stream of a `Demuxer`, see `Demuxer::into_streams`. Keeps the demuxer, which owns the
+/// stream, alive once the packet iterators are dropped.
+pub struct InputStream {
+    stream: Stream,
+    _shared: Rc<RefCell<Shared>>,
+}
+
+impl Deref for InputStream {
+    type Target = Stream;
+
+    fn deref(&self) -> &Self::Target {
+        &self.stream
+    }
+}
+
+/// The packets of one stream of a `Demuxer`, see `Demuxer::into_streams`.
+pub struct StreamPackets {
+    shared: Rc<RefCell<Shared>>,
+    index: usize,
+}
+
+impl StreamPackets {
+    pub(crate) fn split(demuxer: Demuxer) -> Vec<(InputStream, Self)> {
+        let streams = demuxer.streams();
+        let shared = Rc::new(RefCell::new(Shared {
+            queues: streams.iter().map(|_| Some(VecDeque::new())).collect(),
+            demuxer,
+        }));
+        streams
+            .into_iter()
+            .enumerate()
+            .map(|(index, stream)| {
+                let input = InputStream {
+                    stream,
+                    _shared: shared.clone(),
+                };
+                let packets = StreamPackets {
+                    shared: shared.clone(),
+                    index,
+                };
+                (input, packets)
+            })
+            .collect()
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl Iterator for StreamPackets {
+    type Item = Result<Packet, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut shared = self.shared.borrow_mut();
+        loop {
+            if let Some(packet) = shared.queues[self.index].as_mut()?.pop_front() {
+                return Some(Ok(packet));
+            }
+            let packet = match shared.demuxer.next()? {
+                Ok(packet) => packet,
+                Err(e) => return Some(Err(e)),
+            };
+            match packet.stream_index() as usize {
+                index if index == self.index => return Some(Ok(packet)),
+                index => {
+                    if let Some(Some(queue)) = shared.queues.get_mut(index) {
+                        queue.push_back(packet);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for StreamPackets {
+    fn drop(&mut self) {
+        self.shared.borrow_mut().queues[self.index] = None;
+    }
+}
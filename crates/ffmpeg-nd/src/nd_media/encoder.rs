@@ -1,11 +1,13 @@
+mod audio;
 mod video;
 
 use crate::codec::{Codec, Context, Name};
 use crate::util::Error;
 use crate::util::MediaType;
+pub use audio::AudioConfig;
 use core::ops::{Deref, DerefMut};
 use ffi::*;
-use video::*;
+pub use video::VideoConfig;
 
 pub struct Encoder(pub Context);
 
@@ -13,6 +15,10 @@ impl Encoder {
     pub fn video_config() -> VideoConfig {
         VideoConfig::default()
     }
+
+    pub fn audio_config() -> AudioConfig {
+        AudioConfig::default()
+    }
 }
 
 impl Deref for Encoder {
@@ -43,7 +49,28 @@ impl TryFrom<&VideoConfig> for Encoder {
             // .bit_rate(400000)
             .gop_size(*config.framerate * 2)
             .pix_fmt(config.pixel_format)
+            .colorspace(config.color_matrix)
+            .color_range(config.color_range)
             .max_b_frames(2)
+            .options(config.options.clone())
+            .build()?;
+
+        Ok(c.try_into()?)
+    }
+}
+
+impl TryFrom<&AudioConfig> for Encoder {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(config: &AudioConfig) -> Result<Self, Self::Error> {
+        let c = Into::<Name>::into(config.codec)
+            .try_into_audio_encode_context_builder()?
+            .sample_rate(*config.sample_rate)
+            .time_base([1, *config.sample_rate])
+            .sample_fmt(config.sample_format)
+            .ch_layout(config.channel_layout)
+            .bit_rate(*config.bit_rate)
+            .options(config.options.clone())
             .build()?;
 
         Ok(c.try_into()?)
@@ -94,4 +121,32 @@ mod tests {
         let encoder: Encoder = Name::default().default_video_context().try_into().unwrap();
         let _pkt_iter = encoder.into_iter();
     }
+
+    #[test]
+    fn test_options() {
+        use crate::Dictionary;
+
+        let mut video_config = Encoder::video_config();
+        video_config.change_codec_to_h264();
+        let options = [("preset", "ultrafast"), ("not_an_option", "1")];
+        video_config.change_options_to(Dictionary::try_from(options).unwrap());
+        let mut audio_config = Encoder::audio_config();
+        let options = [("aac_coder", "fast"), ("not_an_option", "1")];
+        audio_config.change_options_to(Dictionary::try_from(options).unwrap());
+
+        for result in [
+            Encoder::try_from(&video_config),
+            Encoder::try_from(&audio_config),
+        ] {
+            let Err(err) = result else {
+                panic!("opened with an unknown option");
+            };
+            assert!(
+                matches!(err.downcast_ref::<Error>(), Some(Error::UnusedOptions(keys)) if keys == ["not_an_option"])
+            );
+        }
+
+        video_config.change_options_to(Dictionary::try_from([("preset", "ultrafast")]).unwrap());
+        Encoder::try_from(&video_config).unwrap();
+    }
 }
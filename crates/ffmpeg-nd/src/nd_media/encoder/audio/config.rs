@@ -0,0 +1,137 @@
+mod channel_layout;
+mod codec;
+mod error;
+mod presets;
+mod sample_format;
+
+use channel_layout::*;
+pub use codec::*;
+use error::AudioConfigError;
+use presets::*;
+use sample_format::*;
+
+use crate::util::Dictionary;
+
+#[derive(Default)]
+pub struct AudioConfig {
+    pub(crate) codec: Codec,
+    pub(crate) sample_format: SampleFormat,
+    pub(crate) sample_rate: SampleRate,
+    pub(crate) channel_layout: ChannelLayout,
+    pub(crate) bit_rate: BitRate,
+    pub(crate) options: Dictionary,
+}
+
+impl AudioConfig {
+    pub fn nb_channels(&self) -> u16 {
+        self.channel_layout.nb_channels()
+    }
+}
+
+impl AudioConfig {
+    // codec
+    /// Change the codec to AAC and the sample format to FLTP.
+    pub fn change_codec_to_aac(&mut self) {
+        self.codec.change_to_aac();
+        self.sample_format.change_to_fltp();
+    }
+    /// Change the codec to FLAC and the sample format to S16.
+    pub fn change_codec_to_flac(&mut self) {
+        self.codec.change_to_flac();
+        self.sample_format.change_to_s16();
+    }
+    /// Change the codec to Opus, the sample format to FLT and the sample rate to 48 kHz.
+    #[cfg(feature = "opus")]
+    pub fn change_codec_to_opus(&mut self) {
+        self.codec.change_to_opus();
+        self.sample_format.change_to_flt();
+        self.sample_rate.change_to_48000();
+    }
+
+    // sample rate
+    pub fn change_sample_rate_to_44100(&mut self) -> Result<(), AudioConfigError> {
+        match self.codec {
+            #[cfg(feature = "opus")]
+            Codec::Opus => Err(AudioConfigError::UnsupportedSampleRate),
+            _ => Ok(()),
+        }?;
+        self.sample_rate.change_to_44100();
+        Ok(())
+    }
+    pub fn change_sample_rate_to_48000(&mut self) {
+        self.sample_rate.change_to_48000();
+    }
+
+    // channel layout
+    pub fn change_channel_layout_to_mono(&mut self) {
+        self.channel_layout.change_to_mono();
+    }
+    pub fn change_channel_layout_to_stereo(&mut self) {
+        self.channel_layout.change_to_stereo();
+    }
+
+    // bit rate
+    /// Target bit rate in bits per second. Ignored by lossless codecs.
+    pub fn change_bit_rate_to(&mut self, value: u32) {
+        self.bit_rate = BitRate(value);
+    }
+
+    // options
+    /// Codec and private codec options, e.g. `aac_coder` for AAC. `Encoder::try_from` fails on
+    /// keys the codec does not know.
+    pub fn change_options_to(&mut self, value: Dictionary) {
+        self.options = value;
+    }
+
+    // sample format
+    pub fn change_sample_format_to_fltp(&mut self) -> Result<(), AudioConfigError> {
+        match self.codec {
+            Codec::Aac => Ok(()),
+            _ => Err(AudioConfigError::UnsupportedSampleFormat),
+        }?;
+        self.sample_format.change_to_fltp();
+        Ok(())
+    }
+    pub fn change_sample_format_to_flt(&mut self) -> Result<(), AudioConfigError> {
+        match self.codec {
+            #[cfg(feature = "opus")]
+            Codec::Opus => Ok(()),
+            _ => Err(AudioConfigError::UnsupportedSampleFormat),
+        }?;
+        self.sample_format.change_to_flt();
+        Ok(())
+    }
+    pub fn change_sample_format_to_s16(&mut self) -> Result<(), AudioConfigError> {
+        match self.codec {
+            Codec::Aac => Err(AudioConfigError::UnsupportedSampleFormat),
+            _ => Ok(()),
+        }?;
+        self.sample_format.change_to_s16();
+        Ok(())
+    }
+    pub fn change_sample_format_to_s32(&mut self) -> Result<(), AudioConfigError> {
+        match self.codec {
+            Codec::Flac => Ok(()),
+            _ => Err(AudioConfigError::UnsupportedSampleFormat),
+        }?;
+        self.sample_format.change_to_s32();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test() {
+        let mut config = AudioConfig::default();
+        config.change_sample_rate_to_44100().unwrap();
+        config.change_channel_layout_to_mono();
+        config.change_bit_rate_to(96000);
+        config.change_sample_format_to_s16().unwrap_err();
+
+        config.change_codec_to_flac();
+        config.change_sample_format_to_s32().unwrap();
+        config.change_sample_format_to_fltp().unwrap_err();
+    }
+}
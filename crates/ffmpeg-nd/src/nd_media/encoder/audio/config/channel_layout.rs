@@ -0,0 +1,32 @@
+use crate::util;
+
+#[derive(Default, Clone, Copy)]
+pub enum ChannelLayout {
+    Mono,
+    #[default]
+    Stereo,
+}
+
+impl ChannelLayout {
+    pub fn nb_channels(&self) -> u16 {
+        match self {
+            ChannelLayout::Mono => 1,
+            ChannelLayout::Stereo => 2,
+        }
+    }
+    pub fn change_to_mono(&mut self) {
+        *self = ChannelLayout::Mono;
+    }
+    pub fn change_to_stereo(&mut self) {
+        *self = ChannelLayout::Stereo;
+    }
+}
+
+impl Into<util::ChannelLayout> for ChannelLayout {
+    fn into(self) -> util::ChannelLayout {
+        match self {
+            ChannelLayout::Mono => util::ChannelLayout::mono(),
+            ChannelLayout::Stereo => util::ChannelLayout::stereo(),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::codec::Name;
+
+#[derive(Default, Copy, Clone)]
+pub enum Codec {
+    #[default]
+    Aac,
+    Flac,
+    #[cfg(feature = "opus")]
+    Opus,
+}
+
+impl Codec {
+    pub fn change_to_aac(&mut self) {
+        *self = Codec::Aac;
+    }
+    pub fn change_to_flac(&mut self) {
+        *self = Codec::Flac;
+    }
+    #[cfg(feature = "opus")]
+    pub fn change_to_opus(&mut self) {
+        *self = Codec::Opus;
+    }
+}
+
+impl From<Codec> for Name {
+    fn from(codec: Codec) -> Name {
+        match codec {
+            Codec::Aac => Name::Aac,
+            Codec::Flac => Name::Flac,
+            #[cfg(feature = "opus")]
+            Codec::Opus => Name::LibOpus,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use std::error::Error;
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub enum AudioConfigError {
+    UnsupportedSampleFormat,
+    UnsupportedSampleRate,
+}
+
+impl Display for AudioConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AudioConfigError::UnsupportedSampleFormat => {
+                write!(f, "Unsupported sample format")
+            }
+            AudioConfigError::UnsupportedSampleRate => {
+                write!(f, "Unsupported sample rate")
+            }
+        }
+    }
+}
+
+impl Error for AudioConfigError {}
@@ -0,0 +1,5 @@
+mod bit_rate;
+mod sample_rate;
+
+pub use bit_rate::*;
+pub use sample_rate::*;
@@ -0,0 +1,19 @@
+use core::ops::Deref;
+
+/// Bits per second.
+#[derive(Clone, Copy)]
+pub struct BitRate(pub u32);
+
+impl Default for BitRate {
+    fn default() -> Self {
+        BitRate(128_000)
+    }
+}
+
+impl Deref for BitRate {
+    type Target = u32;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,49 @@
+use core::ops::Deref;
+
+#[derive(Clone, Copy)]
+pub struct SampleRate(pub u32);
+
+impl Default for SampleRate {
+    fn default() -> Self {
+        Preset::default().into()
+    }
+}
+
+impl SampleRate {
+    pub fn hz44100() -> Self {
+        Preset::Hz44100.into()
+    }
+    pub fn hz48000() -> Self {
+        Preset::Hz48000.into()
+    }
+    pub fn change_to_44100(&mut self) {
+        *self = Self::hz44100();
+    }
+    pub fn change_to_48000(&mut self) {
+        *self = Self::hz48000();
+    }
+}
+
+impl Deref for SampleRate {
+    type Target = u32;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Default)]
+enum Preset {
+    Hz44100,
+    #[default]
+    Hz48000,
+}
+
+impl From<Preset> for SampleRate {
+    fn from(preset: Preset) -> Self {
+        match preset {
+            Preset::Hz44100 => SampleRate(44100),
+            Preset::Hz48000 => SampleRate(48000),
+        }
+    }
+}
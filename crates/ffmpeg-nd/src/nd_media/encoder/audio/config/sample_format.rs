@@ -0,0 +1,36 @@
+use crate::util;
+
+#[derive(Default, Clone, Copy)]
+pub enum SampleFormat {
+    #[default]
+    FLTP,
+    FLT,
+    S16,
+    S32,
+}
+
+impl SampleFormat {
+    pub fn change_to_fltp(&mut self) {
+        *self = SampleFormat::FLTP;
+    }
+    pub fn change_to_flt(&mut self) {
+        *self = SampleFormat::FLT;
+    }
+    pub fn change_to_s16(&mut self) {
+        *self = SampleFormat::S16;
+    }
+    pub fn change_to_s32(&mut self) {
+        *self = SampleFormat::S32;
+    }
+}
+
+impl Into<util::SampleFormat> for SampleFormat {
+    fn into(self) -> util::SampleFormat {
+        match self {
+            SampleFormat::FLTP => util::SampleFormat::FLTP,
+            SampleFormat::FLT => util::SampleFormat::FLT,
+            SampleFormat::S16 => util::SampleFormat::S16,
+            SampleFormat::S32 => util::SampleFormat::S32,
+        }
+    }
+}
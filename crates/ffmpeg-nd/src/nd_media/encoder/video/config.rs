@@ -1,19 +1,26 @@
 mod codec;
+mod color;
 mod error;
 mod pixel_format;
 mod presets;
 
 pub use codec::*;
+use color::*;
 use error::VideoConfigError;
 use pixel_format::*;
 use presets::*;
 
+use crate::util::Dictionary;
+
 #[derive(Default)]
 pub struct VideoConfig {
     pub(crate) codec: Codec,
     pub(crate) pixel_format: PixelFormat,
     pub(crate) resolution: Resolution,
     pub(crate) framerate: Framerate,
+    pub(crate) color_matrix: ColorMatrix,
+    pub(crate) color_range: ColorRange,
+    pub(crate) options: Dictionary,
 }
 
 impl VideoConfig {
@@ -63,6 +70,28 @@ impl VideoConfig {
         self.framerate.change_to_60();
     }
 
+    // color
+    /// YUV matrix used when converting RGB input, also signalled in the bitstream.
+    pub fn change_color_matrix_to_bt601(&mut self) {
+        self.color_matrix.change_to_bt601();
+    }
+    pub fn change_color_matrix_to_bt709(&mut self) {
+        self.color_matrix.change_to_bt709();
+    }
+    pub fn change_color_range_to_limited(&mut self) {
+        self.color_range.change_to_limited();
+    }
+    pub fn change_color_range_to_full(&mut self) {
+        self.color_range.change_to_full();
+    }
+
+    // options
+    /// Codec and private codec options, e.g. `preset` for libx264. `Encoder::try_from` fails
+    /// on keys the codec does not know.
+    pub fn change_options_to(&mut self, value: Dictionary) {
+        self.options = value;
+    }
+
     // pixel format
     pub fn change_pixel_format_to_yuv444p(&mut self) -> Result<(), VideoConfigError> {
         match self.codec {
@@ -0,0 +1,51 @@
+use crate::util;
+
+#[derive(Default, Clone, Copy)]
+pub enum ColorMatrix {
+    #[default]
+    Bt601,
+    Bt709,
+}
+
+impl ColorMatrix {
+    pub fn change_to_bt601(&mut self) {
+        *self = ColorMatrix::Bt601;
+    }
+    pub fn change_to_bt709(&mut self) {
+        *self = ColorMatrix::Bt709;
+    }
+}
+
+impl Into<util::ColorSpace> for ColorMatrix {
+    fn into(self) -> util::ColorSpace {
+        match self {
+            ColorMatrix::Bt601 => util::ColorSpace::BT601,
+            ColorMatrix::Bt709 => util::ColorSpace::BT709,
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+pub enum ColorRange {
+    #[default]
+    Limited,
+    Full,
+}
+
+impl ColorRange {
+    pub fn change_to_limited(&mut self) {
+        *self = ColorRange::Limited;
+    }
+    pub fn change_to_full(&mut self) {
+        *self = ColorRange::Full;
+    }
+}
+
+impl Into<util::ColorRange> for ColorRange {
+    fn into(self) -> util::ColorRange {
+        match self {
+            ColorRange::Limited => util::ColorRange::MPEG,
+            ColorRange::Full => util::ColorRange::JPEG,
+        }
+    }
+}
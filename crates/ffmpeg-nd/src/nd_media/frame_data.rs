@@ -1,5 +1,12 @@
-use crate::util::Frame;
+mod convert;
+mod error;
+
+use crate::util::{Frame, PixelFormat};
+pub use convert::InputFormat;
+pub(crate) use convert::convert_from_frame;
+use convert::convert_to_frame;
 use core::slice;
+pub use error::FrameDataError;
 
 pub enum FrameData {
     /// Packed format.
@@ -8,47 +15,174 @@ pub enum FrameData {
     YUV420P(Vec<u8>),
     /// Planar format.
     YUV444P(Vec<u8>),
+    /// Tightly packed planes of any other format with a known layout, e.g. NV12.
+    Planes(PixelFormat, Vec<u8>),
+    /// Interleaved PCM, e.g. LRLR... for stereo.
+    Interleaved(Vec<u8>),
+    /// Planar PCM, one buffer per channel.
+    Planar(Vec<Vec<u8>>),
+    /// Packed RGB to be converted into the frame's pixel format.
+    Convert(InputFormat, Vec<u8>),
 }
 
 impl FrameData {
+    /// Wrap one tightly packed video frame of the given format.
+    pub fn video(pix_fmt: PixelFormat, data: Vec<u8>) -> Option<Self> {
+        match pix_fmt {
+            PixelFormat::RGB24 => Some(FrameData::RGB24(data)),
+            PixelFormat::YUV420P => Some(FrameData::YUV420P(data)),
+            PixelFormat::YUV444P => Some(FrameData::YUV444P(data)),
+            PixelFormat::YUVJ420P
+            | PixelFormat::YUV422P
+            | PixelFormat::NV12
+            | PixelFormat::YUV420P10LE => Some(FrameData::Planes(pix_fmt, data)),
+            PixelFormat::None | PixelFormat::Other(_) => None,
+        }
+    }
+
     #[inline(always)]
-    pub fn set_to_frame(&self, frame: &mut Frame, pts: &mut u32) {
+    pub fn set_to_frame(&self, frame: &mut Frame, pts: &mut u32) -> Result<(), FrameDataError> {
         match self {
-            FrameData::RGB24(data) => {
-                // RGBRGB...
-                let len = frame.pixel_count() * 3;
-                let dst = unsafe { slice::from_raw_parts_mut(frame.data[0], len) };
+            FrameData::RGB24(data) => copy_planes(frame, PixelFormat::RGB24, data)?,
+            FrameData::YUV420P(data) => copy_planes(frame, PixelFormat::YUV420P, data)?,
+            FrameData::YUV444P(data) => copy_planes(frame, PixelFormat::YUV444P, data)?,
+            FrameData::Planes(pix_fmt, data) => copy_planes(frame, *pix_fmt, data)?,
+            FrameData::Convert(input, data) => convert_to_frame(frame, *input, data)?,
+            FrameData::Interleaved(data) => {
+                let sample_size =
+                    frame.sample_fmt().bytes_per_sample() * frame.ch_layout.nb_channels as usize;
+                frame.set_nb_samples(data.len() / sample_size);
+
+                let dst = unsafe { slice::from_raw_parts_mut(frame.data[0], data.len()) };
                 dst.copy_from_slice(data);
             }
-            FrameData::YUV420P(data) => {
-                // YUV420P
-                let plane_size = frame.pixel_count();
-                let (y_plane, rest) = data.split_at(plane_size);
-                let (u_plane, v_plane) = rest.split_at(plane_size / 4);
-                let y_dst = unsafe { slice::from_raw_parts_mut(frame.data[0], plane_size) };
-                let u_dst = unsafe { slice::from_raw_parts_mut(frame.data[1], plane_size / 4) };
-                let v_dst = unsafe { slice::from_raw_parts_mut(frame.data[2], plane_size / 4) };
-                y_dst.copy_from_slice(y_plane);
-                u_dst.copy_from_slice(u_plane);
-                v_dst.copy_from_slice(v_plane);
+            FrameData::Planar(planes) => {
+                let sample_size = frame.sample_fmt().bytes_per_sample();
+                frame.set_nb_samples(planes[0].len() / sample_size);
+
+                // `extended_data` also covers layouts with more channels than `data` can hold.
+                for (i, plane) in planes.iter().enumerate() {
+                    let dst = unsafe {
+                        slice::from_raw_parts_mut(*frame.extended_data.add(i), plane.len())
+                    };
+                    dst.copy_from_slice(plane);
+                }
             }
-            FrameData::YUV444P(data) => {
-                let plane_size = frame.pixel_count();
+        }
 
-                let (y_plane, rest) = data.split_at(plane_size);
-                let (u_plane, v_plane) = rest.split_at(plane_size);
+        frame.set_pts(*pts);
+        *pts += match self {
+            FrameData::Interleaved(_) | FrameData::Planar(_) => frame.nb_samples() as u32,
+            _ => 1,
+        };
+        Ok(())
+    }
+}
 
-                let y_dst = unsafe { slice::from_raw_parts_mut(frame.data[0], plane_size) };
-                let u_dst = unsafe { slice::from_raw_parts_mut(frame.data[1], plane_size) };
-                let v_dst = unsafe { slice::from_raw_parts_mut(frame.data[2], plane_size) };
+/// Copy tightly packed planes row by row into the frame, whose rows are `linesize` apart.
+fn copy_planes(frame: &mut Frame, pix_fmt: PixelFormat, data: &[u8]) -> Result<(), FrameDataError> {
+    let planes = pix_fmt.planes(frame.width().into(), frame.height().into());
+    let expected = pix_fmt.buffer_size(frame.width().into(), frame.height().into());
+    if data.len() != expected {
+        return Err(FrameDataError::InvalidLength {
+            expected,
+            actual: data.len(),
+        });
+    }
 
-                y_dst.copy_from_slice(y_plane);
-                u_dst.copy_from_slice(u_plane);
-                v_dst.copy_from_slice(v_plane);
-            }
+    let mut src = data;
+    for (i, (row_bytes, rows)) in planes.into_iter().enumerate() {
+        let linesize = frame.linesize[i] as usize;
+        let dst = unsafe { slice::from_raw_parts_mut(frame.data[i], linesize * rows) };
+        let (plane, rest) = src.split_at(row_bytes * rows);
+        for (src_row, dst_row) in plane.chunks_exact(row_bytes).zip(dst.chunks_mut(linesize)) {
+            dst_row[..row_bytes].copy_from_slice(src_row);
+        }
+        src = rest;
+    }
+    Ok(())
+}
+
+/// Inverse of `copy_planes`: the planes of a video frame without row padding, back to back.
+pub(crate) fn copy_from_planes(frame: &Frame) -> Vec<u8> {
+    let planes = frame
+        .pix_fmt()
+        .planes(frame.width().into(), frame.height().into());
+    let mut data = Vec::with_capacity(
+        planes
+            .iter()
+            .map(|(row_bytes, rows)| row_bytes * rows)
+            .sum(),
+    );
+    for (i, (row_bytes, rows)) in planes.into_iter().enumerate() {
+        let linesize = frame.linesize[i] as usize;
+        for row in 0..rows {
+            let src =
+                unsafe { slice::from_raw_parts(frame.data[i].add(row * linesize), row_bytes) };
+            data.extend_from_slice(src);
         }
+    }
+    data
+}
 
-        frame.set_pts(*pts);
-        *pts += 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut frame = Frame::new();
+        frame.set_format(PixelFormat::YUV420P);
+        frame.set_width(33);
+        frame.set_height(17);
+        frame.get_buffer().unwrap();
+        assert!(frame.linesize[0] > 33);
+
+        let mut pts = 0;
+        let size = PixelFormat::YUV420P.buffer_size(33, 17);
+        let data = (0..size).map(|i| i as u8).collect::<Vec<u8>>();
+        FrameData::YUV420P(data.clone())
+            .set_to_frame(&mut frame, &mut pts)
+            .unwrap();
+        assert_eq!(pts, 1);
+
+        // Last row of the V plane.
+        let v_row =
+            unsafe { slice::from_raw_parts(frame.data[2].add(8 * frame.linesize[2] as usize), 17) };
+        assert_eq!(v_row, &data[size - 17..]);
+
+        assert_eq!(copy_from_planes(&frame), data);
+
+        let err = FrameData::YUV420P(vec![0u8; size - 1])
+            .set_to_frame(&mut frame, &mut pts)
+            .unwrap_err();
+        assert!(
+            matches!(err, FrameDataError::InvalidLength { expected, actual } if expected == size && actual == size - 1)
+        );
+    }
+
+    #[test]
+    fn test_video() {
+        for pix_fmt in [
+            PixelFormat::YUVJ420P,
+            PixelFormat::YUV422P,
+            PixelFormat::NV12,
+            PixelFormat::YUV420P10LE,
+        ] {
+            let mut frame = Frame::new();
+            frame.set_format(pix_fmt);
+            frame.set_width(33);
+            frame.set_height(17);
+            frame.get_buffer().unwrap();
+
+            let size = pix_fmt.buffer_size(33, 17);
+            let data = (0..size).map(|i| i as u8).collect::<Vec<u8>>();
+            FrameData::video(pix_fmt, data.clone())
+                .unwrap()
+                .set_to_frame(&mut frame, &mut 0)
+                .unwrap();
+            assert_eq!(copy_from_planes(&frame), data, "{:?}", pix_fmt);
+        }
+        assert!(FrameData::video(PixelFormat::None, vec![]).is_none());
     }
 }
@@ -0,0 +1,302 @@
+//! Conversion between packed RGB and the codec's pixel format, backed by `dcv-color-primitives`.
+
+use super::error::FrameDataError;
+#[cfg(feature = "swscale")]
+use crate::swscale::{Interpolation, Scaler};
+use crate::util::{ColorRange, ColorSpace, Frame, PixelFormat};
+use core::slice;
+use dcv_color_primitives as dcp;
+use std::borrow::Cow;
+
+/// Packed RGB layouts accepted in place of the encoder's own pixel format, or produced from decoded frames.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InputFormat {
+    RGB24,
+    RGBA,
+    BGRA,
+}
+
+impl InputFormat {
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            InputFormat::RGB24 => 3,
+            InputFormat::RGBA | InputFormat::BGRA => 4,
+        }
+    }
+
+    /// Byte offsets of red, green and blue within a pixel.
+    fn rgb_offsets(&self) -> [usize; 3] {
+        match self {
+            InputFormat::RGB24 | InputFormat::RGBA => [0, 1, 2],
+            InputFormat::BGRA => [2, 1, 0],
+        }
+    }
+}
+
+/// Convert one packed RGB image into the frame, using the frame's colorspace and range.
+pub(super) fn convert_to_frame(
+    frame: &mut Frame,
+    input: InputFormat,
+    data: &[u8],
+) -> Result<(), FrameDataError> {
+    let (width, height) = (frame.width() as usize, frame.height() as usize);
+    let expected = width * height * input.bytes_per_pixel();
+    if data.len() != expected {
+        return Err(FrameDataError::InvalidLength {
+            expected,
+            actual: data.len(),
+        });
+    }
+
+    let dst_pixel_format = match frame.pix_fmt() {
+        PixelFormat::YUV420P | PixelFormat::YUVJ420P => dcp::PixelFormat::I420,
+        PixelFormat::YUV444P => dcp::PixelFormat::I444,
+        PixelFormat::RGB24 => {
+            copy_to_rgb24(frame, input, data);
+            return Ok(());
+        }
+        pix_fmt => {
+            return Err(FrameDataError::Conversion(format!(
+                "PixelFormat::{:?}",
+                pix_fmt
+            )));
+        }
+    };
+
+    // dcv converts from BGR channel order, so RGB input is swapped first.
+    let (src_pixel_format, src): (_, Cow<[u8]>) = match input {
+        InputFormat::BGRA => (dcp::PixelFormat::Bgra, Cow::Borrowed(data)),
+        InputFormat::RGBA => (dcp::PixelFormat::Bgra, Cow::Owned(swap_red_blue(data, 4))),
+        InputFormat::RGB24 => (dcp::PixelFormat::Bgr, Cow::Owned(swap_red_blue(data, 3))),
+    };
+    let src_format = dcp::ImageFormat {
+        pixel_format: src_pixel_format,
+        color_space: dcp::ColorSpace::Rgb,
+        num_planes: 1,
+    };
+    let dst_format = dcp::ImageFormat {
+        pixel_format: dst_pixel_format,
+        color_space: color_space(frame.colorspace(), frame.color_range()),
+        num_planes: 3,
+    };
+
+    let planes = frame.pix_fmt().planes(width, height);
+    let dst_strides: Vec<usize> = (0..planes.len())
+        .map(|i| frame.linesize[i] as usize)
+        .collect();
+    let mut dst_buffers: Vec<&mut [u8]> = planes
+        .iter()
+        .enumerate()
+        .map(|(i, (_, rows))| unsafe {
+            slice::from_raw_parts_mut(frame.data[i], dst_strides[i] * rows)
+        })
+        .collect();
+
+    dcp::convert_image(
+        width as u32,
+        height as u32,
+        &src_format,
+        None,
+        &[&src[..]],
+        &dst_format,
+        Some(&dst_strides[..]),
+        &mut dst_buffers,
+    )
+    .map_err(|e| FrameDataError::Conversion(format!("{:?}", e)))
+}
+
+/// Convert a decoded frame into one tightly packed RGB image, using the frame's colorspace and range.
+pub(crate) fn convert_from_frame(
+    frame: &Frame,
+    output: InputFormat,
+) -> Result<Vec<u8>, FrameDataError> {
+    let (width, height) = (frame.width() as usize, frame.height() as usize);
+
+    let src_pixel_format = match frame.pix_fmt() {
+        PixelFormat::YUV420P | PixelFormat::YUVJ420P => dcp::PixelFormat::I420,
+        PixelFormat::YUV444P => dcp::PixelFormat::I444,
+        PixelFormat::RGB24 => return Ok(copy_from_rgb24(frame, output)),
+        #[cfg(feature = "swscale")]
+        _ => return Ok(copy_from_rgb24(&scale_to_rgb24(frame)?, output)),
+        #[cfg(not(feature = "swscale"))]
+        pix_fmt => {
+            return Err(FrameDataError::Conversion(format!(
+                "PixelFormat::{:?}",
+                pix_fmt
+            )));
+        }
+    };
+    let src_format = dcp::ImageFormat {
+        pixel_format: src_pixel_format,
+        color_space: color_space(frame.colorspace(), frame.color_range()),
+        num_planes: 3,
+    };
+    let dst_format = dcp::ImageFormat {
+        pixel_format: dcp::PixelFormat::Bgra,
+        color_space: dcp::ColorSpace::Rgb,
+        num_planes: 1,
+    };
+
+    let planes = frame.pix_fmt().planes(width, height);
+    let src_strides: Vec<usize> = (0..planes.len())
+        .map(|i| frame.linesize[i] as usize)
+        .collect();
+    let src_buffers: Vec<&[u8]> = planes
+        .iter()
+        .enumerate()
+        .map(|(i, (_, rows))| unsafe {
+            slice::from_raw_parts(frame.data[i], src_strides[i] * rows)
+        })
+        .collect();
+
+    let mut bgra = vec![0u8; width * height * 4];
+    dcp::convert_image(
+        width as u32,
+        height as u32,
+        &src_format,
+        Some(&src_strides[..]),
+        &src_buffers,
+        &dst_format,
+        None,
+        &mut [&mut bgra[..]],
+    )
+    .map_err(|e| FrameDataError::Conversion(format!("{:?}", e)))?;
+
+    // dcv converts to BGR channel order, so other layouts are swapped afterwards.
+    Ok(match output {
+        InputFormat::BGRA => bgra,
+        InputFormat::RGBA => swap_red_blue(&bgra, 4),
+        InputFormat::RGB24 => bgra
+            .chunks_exact(4)
+            .flat_map(|pixel| [pixel[2], pixel[1], pixel[0]])
+            .collect(),
+    })
+}
+
+/// Convert a frame of any other pixel format swscale reads into RGB24 of the same size.
+#[cfg(feature = "swscale")]
+fn scale_to_rgb24(frame: &Frame) -> Result<Frame, FrameDataError> {
+    let (width, height) = (frame.width(), frame.height());
+    Scaler::new(
+        (width, height, frame.pix_fmt()),
+        (width, height, PixelFormat::RGB24),
+        Interpolation::default(),
+    )
+    .and_then(|mut scaler| scaler.scale(frame))
+    .map_err(|e| FrameDataError::Conversion(e.to_string()))
+}
+
+/// BT.601 unless BT.709 is requested; limited range unless full range is requested.
+fn color_space(colorspace: ColorSpace, range: ColorRange) -> dcp::ColorSpace {
+    match (colorspace, range) {
+        (ColorSpace::BT709, ColorRange::JPEG) => dcp::ColorSpace::Bt709FR,
+        (ColorSpace::BT709, _) => dcp::ColorSpace::Bt709,
+        (_, ColorRange::JPEG) => dcp::ColorSpace::Bt601FR,
+        (_, _) => dcp::ColorSpace::Bt601,
+    }
+}
+
+fn swap_red_blue(data: &[u8], bytes_per_pixel: usize) -> Vec<u8> {
+    let mut swapped = data.to_vec();
+    for pixel in swapped.chunks_exact_mut(bytes_per_pixel) {
+        pixel.swap(0, 2);
+    }
+    swapped
+}
+
+fn copy_to_rgb24(frame: &mut Frame, input: InputFormat, data: &[u8]) {
+    let (width, height) = (frame.width() as usize, frame.height() as usize);
+    let linesize = frame.linesize[0] as usize;
+    let dst = unsafe { slice::from_raw_parts_mut(frame.data[0], linesize * height) };
+    let [r, g, b] = input.rgb_offsets();
+    let src_row_bytes = width * input.bytes_per_pixel();
+
+    for (src_row, dst_row) in data
+        .chunks_exact(src_row_bytes)
+        .zip(dst.chunks_mut(linesize))
+    {
+        for (src, dst) in src_row
+            .chunks_exact(input.bytes_per_pixel())
+            .zip(dst_row[..width * 3].chunks_exact_mut(3))
+        {
+            dst.copy_from_slice(&[src[r], src[g], src[b]]);
+        }
+    }
+}
+
+fn copy_from_rgb24(frame: &Frame, output: InputFormat) -> Vec<u8> {
+    let (width, height) = (frame.width() as usize, frame.height() as usize);
+    let linesize = frame.linesize[0] as usize;
+    let src = unsafe { slice::from_raw_parts(frame.data[0], linesize * height) };
+    let [r, g, b] = output.rgb_offsets();
+    let mut data = vec![255u8; width * height * output.bytes_per_pixel()];
+
+    let dst_row_bytes = width * output.bytes_per_pixel();
+
+    for (src_row, dst_row) in src
+        .chunks(linesize)
+        .zip(data.chunks_exact_mut(dst_row_bytes))
+    {
+        for (src, dst) in src_row[..width * 3]
+            .chunks_exact(3)
+            .zip(dst_row.chunks_exact_mut(output.bytes_per_pixel()))
+        {
+            (dst[r], dst[g], dst[b]) = (src[0], src[1], src[2]);
+        }
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut frame = Frame::new();
+        frame.set_format(PixelFormat::YUV420P);
+        frame.set_width(64);
+        frame.set_height(48);
+        frame.set_colorspace(ColorSpace::BT709);
+        frame.set_color_range(ColorRange::MPEG);
+        frame.get_buffer().unwrap();
+
+        // Opaque white in every input layout converts to limited-range white (235) and neutral chroma.
+        for input in [InputFormat::RGB24, InputFormat::RGBA, InputFormat::BGRA] {
+            let data = vec![255u8; 64 * 48 * input.bytes_per_pixel()];
+            convert_to_frame(&mut frame, input, &data).unwrap();
+            let y = unsafe { *frame.data[0] };
+            let u = unsafe { *frame.data[1] };
+            assert!((234..=236).contains(&y), "{:?}: y = {}", input, y);
+            assert!((127..=129).contains(&u), "{:?}: u = {}", input, u);
+        }
+
+        convert_to_frame(&mut frame, InputFormat::RGBA, &[0u8; 4]).unwrap_err();
+
+        // And back again.
+        let rgba = convert_from_frame(&frame, InputFormat::RGBA).unwrap();
+        assert_eq!(rgba.len(), 64 * 48 * 4);
+        assert!(rgba.iter().all(|&value| value >= 253), "{:?}", &rgba[..4]);
+        let rgb = convert_from_frame(&frame, InputFormat::RGB24).unwrap();
+        assert_eq!(rgb.len(), 64 * 48 * 3);
+    }
+
+    #[cfg(feature = "swscale")]
+    #[test]
+    fn should_convert_other_formats_from_frame() {
+        for pix_fmt in [
+            PixelFormat::YUV422P,
+            PixelFormat::NV12,
+            PixelFormat::YUV420P10LE,
+            PixelFormat::Other(ffi::AVPixelFormat_AV_PIX_FMT_GBRP),
+        ] {
+            let mut frame = Frame::new();
+            frame.set_format(pix_fmt);
+            frame.set_width(64);
+            frame.set_height(48);
+            frame.get_buffer().unwrap();
+            let rgb = convert_from_frame(&frame, InputFormat::RGB24).unwrap();
+            assert_eq!(rgb.len(), 64 * 48 * 3, "{:?}", pix_fmt);
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use std::error::Error;
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub enum FrameDataError {
+    /// The data does not match the size of one frame in the encoder's format.
+    InvalidLength { expected: usize, actual: usize },
+    /// RGB input could not be converted into the encoder's pixel format.
+    Conversion(String),
+}
+
+impl Display for FrameDataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameDataError::InvalidLength { expected, actual } => {
+                write!(
+                    f,
+                    "Invalid frame data length: expected {} bytes, got {}",
+                    expected, actual
+                )
+            }
+            FrameDataError::Conversion(msg) => write!(f, "Color conversion failed: {}", msg),
+        }
+    }
+}
+
+impl Error for FrameDataError {}
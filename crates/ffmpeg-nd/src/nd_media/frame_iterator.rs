@@ -0,0 +1,116 @@
+use crate::codec::{Context, Packet};
+use crate::util::Frame;
+use core::iter::Iterator;
+use core::ops::Deref;
+use kanal;
+
+/// Decoding counterpart of `PacketIterator`: packets go in through `sender()`, frames come out.
+pub struct FrameIterator {
+    context: Context,
+    packet_chan: (kanal::Sender<Option<Packet>>, kanal::Receiver<Option<Packet>>),
+    eof: bool,
+}
+
+impl FrameIterator {
+    /// Send `None` once all packets have been sent to flush the decoder.
+    pub fn sender(&self) -> kanal::Sender<Option<Packet>> {
+        self.packet_chan.0.clone()
+    }
+}
+
+impl Iterator for FrameIterator {
+    type Item = Result<Frame, Box<dyn std::error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.eof {
+            return None;
+        }
+
+        let result: Result<Option<Frame>, Box<dyn std::error::Error>> = (|| {
+            loop {
+                let mut frame = Frame::new();
+                match self.context.receive_frame(&mut frame) {
+                    Ok(()) => return Ok(Some(frame)),
+                    Err(e) if e.is_eof() => {
+                        self.eof = true;
+                        return Ok(None);
+                    }
+                    Err(e) if e.is_eagain() => {}
+                    Err(e) => return Err(e.into()),
+                }
+
+                match self.packet_chan.1.recv()? {
+                    None => {
+                        self.packet_chan.1.close()?;
+                        self.context.send_packet_eof()?;
+                    }
+                    Some(packet) => self.context.send_packet(&packet)?,
+                };
+            }
+        })();
+
+        result.transpose()
+    }
+}
+
+impl Deref for FrameIterator {
+    type Target = Context;
+
+    fn deref(&self) -> &Self::Target {
+        &self.context
+    }
+}
+
+impl From<Context> for FrameIterator {
+    fn from(context: Context) -> Self {
+        FrameIterator {
+            context,
+            packet_chan: kanal::bounded::<Option<Packet>>(8),
+            eof: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        use crate::codec::{Id, Name, find_decoder};
+        use crate::nd_media::packet_iterator::PacketIterator;
+        use crate::nd_media::Decoder;
+
+        let pkt_iter = PacketIterator::from(Name::default().default_video_context());
+        let plane_size = pkt_iter.width() as usize * pkt_iter.height() as usize;
+        let frame_data_sender = pkt_iter.sender();
+
+        std::thread::spawn(move || {
+            for _ in 0..30 {
+                frame_data_sender.send(Some(vec![0u8; plane_size * 3])).unwrap();
+            }
+            frame_data_sender.send(None).unwrap()
+        });
+
+        let packets = pkt_iter
+            .map(|result| result.map(|pkt| (*pkt).clone()))
+            .collect::<Result<Vec<Packet>, _>>()
+            .unwrap();
+
+        let decoder: Decoder = find_decoder(&Id::AV1).unwrap().try_into().unwrap();
+        let frame_iter = decoder.into_iter();
+        let packet_sender = frame_iter.sender();
+
+        std::thread::spawn(move || {
+            for packet in packets {
+                packet_sender.send(Some(packet)).unwrap();
+            }
+            packet_sender.send(None).unwrap()
+        });
+
+        let frames = frame_iter.collect::<Result<Vec<Frame>, _>>().unwrap();
+        assert_eq!(frames.len(), 30);
+        assert_eq!(frames[0].width(), 320);
+        assert_eq!(frames[0].height(), 240);
+    }
+}
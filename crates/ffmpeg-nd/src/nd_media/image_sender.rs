@@ -0,0 +1,164 @@
+//! Feeds `image` crate images to a video `PacketIterator`, resized to the encoder's resolution.
+
+use image::buffer::ConvertBuffer;
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, Frames, Rgba, RgbImage, RgbaImage};
+
+/// How images whose size differs from the encoder's are fitted into each frame.
+#[derive(Default, Clone, Copy)]
+pub enum Resize {
+    /// Keep the aspect ratio and pad with black.
+    #[default]
+    Fit,
+    /// Scale to exactly the frame size.
+    Stretch,
+}
+
+/// Obtained from `PacketIterator::image_sender`, which switches the iterator to RGBA input.
+#[derive(Clone)]
+pub struct ImageSender {
+    sender: kanal::Sender<Option<Vec<u8>>>,
+    width: u32,
+    height: u32,
+    /// Frames per second of the encoder.
+    framerate: f64,
+    resize: Resize,
+    filter: FilterType,
+}
+
+impl ImageSender {
+    pub(crate) fn new(
+        sender: kanal::Sender<Option<Vec<u8>>>,
+        width: u32,
+        height: u32,
+        framerate: f64,
+    ) -> Self {
+        ImageSender {
+            sender,
+            width,
+            height,
+            framerate,
+            resize: Resize::default(),
+            filter: FilterType::Triangle,
+        }
+    }
+
+    pub fn resize(mut self, value: Resize) -> Self {
+        self.resize = value;
+        self
+    }
+
+    pub fn filter(mut self, value: FilterType) -> Self {
+        self.filter = value;
+        self
+    }
+}
+
+impl ImageSender {
+    /// Send one video frame.
+    pub fn send_rgba_image(&self, image: &RgbaImage) -> Result<(), Box<dyn std::error::Error>> {
+        let data = if image.dimensions() == (self.width, self.height) {
+            image.as_raw().clone()
+        } else {
+            self.fit(image).into_raw()
+        };
+        Ok(self.sender.send(Some(data))?)
+    }
+
+    /// Send one video frame.
+    pub fn send_rgb_image(&self, image: &RgbImage) -> Result<(), Box<dyn std::error::Error>> {
+        self.send_rgba_image(&image.convert())
+    }
+
+    /// Send one video frame.
+    pub fn send_dynamic_image(&self, image: &DynamicImage) -> Result<(), Box<dyn std::error::Error>> {
+        self.send_rgba_image(&image.to_rgba8())
+    }
+
+    /// Send an animation, repeating or dropping its frames to follow their delays at the
+    /// encoder's frame rate. Returns the number of video frames sent.
+    pub fn send_frames(&self, frames: Frames) -> Result<u64, Box<dyn std::error::Error>> {
+        let mut end_ms = 0.0;
+        let mut sent = 0;
+        for frame in frames {
+            let frame = frame?;
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            end_ms += numer as f64 / denom as f64;
+
+            let (left, top) = (frame.left(), frame.top());
+            let buffer = frame.into_buffer();
+            let canvas;
+            let image = if (left, top) == (0, 0) {
+                &buffer
+            } else {
+                let mut full = RgbaImage::new(left + buffer.width(), top + buffer.height());
+                imageops::overlay(&mut full, &buffer, left.into(), top.into());
+                canvas = full;
+                &canvas
+            };
+
+            while (sent as f64) * 1000.0 / self.framerate < end_ms {
+                self.send_rgba_image(image)?;
+                sent += 1;
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Signal the end of input to flush the encoder.
+    pub fn finish(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(self.sender.send(None)?)
+    }
+
+    fn fit(&self, image: &RgbaImage) -> RgbaImage {
+        match self.resize {
+            Resize::Stretch => imageops::resize(image, self.width, self.height, self.filter),
+            Resize::Fit => {
+                let scale = f64::min(
+                    self.width as f64 / image.width() as f64,
+                    self.height as f64 / image.height() as f64,
+                );
+                let width = ((image.width() as f64 * scale).round() as u32).clamp(1, self.width);
+                let height = ((image.height() as f64 * scale).round() as u32).clamp(1, self.height);
+                let resized = imageops::resize(image, width, height, self.filter);
+
+                let mut canvas = RgbaImage::from_pixel(self.width, self.height, Rgba([0, 0, 0, 255]));
+                let x = (self.width - width) / 2;
+                let y = (self.height - height) / 2;
+                imageops::overlay(&mut canvas, &resized, x.into(), y.into());
+                canvas
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let (sender, receiver) = kanal::unbounded();
+        let image_sender = ImageSender::new(sender, 320, 240, 30.0);
+
+        let image = RgbImage::from_fn(200, 200, |x, y| image::Rgb([x as u8, y as u8, 0]));
+        image_sender.send_rgb_image(&image).unwrap();
+        let data = receiver.recv().unwrap().unwrap();
+        assert_eq!(data.len(), 320 * 240 * 4);
+        // Letterboxed on the left.
+        assert_eq!(&data[..4], &[0, 0, 0, 255]);
+
+        let frames = (0..3).map(|_| {
+            Ok(image::Frame::from_parts(
+                RgbaImage::new(320, 240),
+                0,
+                0,
+                image::Delay::from_numer_denom_ms(100, 1),
+            ))
+        });
+        let sent = image_sender.send_frames(Frames::new(Box::new(frames))).unwrap();
+        assert_eq!(sent, 9);
+        image_sender.finish().unwrap();
+        assert_eq!(receiver.len(), 10);
+    }
+}
@@ -1,90 +1,220 @@
+mod builder;
+mod fragment;
+mod live;
+mod packaging;
+mod rtp;
+mod segmenter;
+mod source;
+mod stream_options;
+mod tee;
+
 use super::packet_iterator::PacketIterator;
-use crate::format::{Context, Stream};
-use crate::util::{Error, compare_ts};
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::codec::Packet;
+use crate::format::{Context, Format, Stream};
+use crate::util::{Dictionary, Error, compare_ts};
+pub use builder::{MuxerBuilder, StreamHandle};
+pub use fragment::{Fragment, FragmentOptions};
+pub use live::LiveOptions;
+pub use packaging::{Packaging, PackagingOptions, PlaylistType, SegmentFormat};
+use rtp::Rtp;
+use segmenter::Segmenter;
+pub use segmenter::{Segment, SegmentOptions};
+use source::Source;
+pub use stream_options::StreamOptions;
+pub use tee::FailurePolicy;
+use tee::Tee;
 
-#[derive(Default)]
 pub struct Muxer {
     context: Context,
-    streams: Vec<(Stream, PacketIterator)>,
+    streams: Vec<(Stream, Source)>,
+    /// Passed to `write_header`, e.g. `brand`.
+    header_options: Dictionary,
+    target: Target,
+    /// Extra outputs that get a copy of every packet.
+    tees: Vec<Tee>,
 }
 
 impl Muxer {
+    /// Mux into the opened `context`. To abort `mux` with an `Interrupt`, pass it to
+    /// `Context::set_interrupt` before opening the context.
     pub fn new(context: Context) -> Self {
-        Self {
-            context,
-            streams: Vec::new(),
-        }
+        MuxerBuilder::from(context).build()
     }
 
-    // TODO: Consider adding this method to the struct
-    #[cfg(test)]
-    pub fn add_stream_from_pkt_iter(&mut self, pkt_iter: PacketIterator) {
-        let stream: Stream = (&mut self.context, &*pkt_iter).try_into().unwrap();
-        self.streams.push((stream, pkt_iter));
-    }
-
-    fn next_stream(&mut self) -> (usize, &mut (Stream, PacketIterator)) {
-        self.streams
-            .iter_mut()
-            .enumerate()
-            .min_by(|(_, (_, a)), (_, (_, b))| {
-                compare_ts(a.next_pts(), a.time_base(), b.next_pts(), b.time_base())
-            })
-            .unwrap()
+    pub fn builder(format: Format) -> Result<MuxerBuilder, Error> {
+        MuxerBuilder::new(format)
     }
 
+    /// Write the packets of all streams in DTS order. Each stream keeps its next packet at hand,
+    /// so encoder delay on one stream does not reorder the output.
     pub fn mux(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        self.context.write_header()?;
+        let options = core::mem::take(&mut self.header_options);
+        match &mut self.target {
+            Target::Context => write_header(&mut self.context, options)?,
+            Target::Segments(segmenter) => segmenter.set_header_options(options),
+            Target::Rtp(rtp) => rtp.open(&self.context, options)?,
+        }
+        for tee in &mut self.tees {
+            tee.open(&self.context)?;
+        }
+
+        let mut pending: Vec<Pending> = self.streams.iter().map(|_| Pending::Empty).collect();
         loop {
-            let (index, (stream, pkt_iter)) = self.next_stream();
-            match pkt_iter.next() {
-                Some(Ok(mut pkt)) => {
-                    let pkt = Rc::get_mut(&mut pkt).unwrap();
-                    pkt.rescale_ts(&pkt_iter.time_base(), &stream.time_base());
-                    pkt.set_stream_index(stream.index());
-                    self.context.interleaved_write_frame(pkt)?;
+            for ((stream, source), pending) in self.streams.iter_mut().zip(pending.iter_mut()) {
+                if let Pending::Empty = pending {
+                    *pending = match source.next().transpose()? {
+                        Some(mut pkt) => {
+                            pkt.rescale_ts(&source.time_base(), &stream.time_base());
+                            pkt.set_stream_index(stream.index());
+                            Pending::Packet(pkt)
+                        }
+                        None => Pending::Finished,
+                    };
                 }
-                Some(Err(e)) => return Err(e),
-                None => {
-                    self.streams.remove(index);
-                    if self.streams.len() == 0 {
-                        break;
-                    }
+            }
+
+            let next = pending
+                .iter()
+                .zip(&self.streams)
+                .enumerate()
+                .filter_map(|(index, (pending, (stream, _)))| match pending {
+                    Pending::Packet(pkt) => Some((index, pkt.dts_or_pts(), stream.time_base())),
+                    _ => None,
+                })
+                .min_by(|(_, ts_a, tb_a), (_, ts_b, tb_b)| compare_ts(*ts_a, *tb_a, *ts_b, *tb_b));
+            let Some((index, _, _)) = next else {
+                break;
+            };
+
+            if let Pending::Packet(mut pkt) =
+                core::mem::replace(&mut pending[index], Pending::Empty)
+            {
+                for tee in &mut self.tees {
+                    tee.write(&self.context, &pkt)?;
+                }
+                match &mut self.target {
+                    Target::Context => self.context.interleaved_write_frame(&mut pkt)?,
+                    Target::Segments(segmenter) => segmenter.write(&self.context, pkt)?,
+                    Target::Rtp(rtp) => rtp.write(&self.context, pkt)?,
                 }
             }
         }
-        self.context.write_trailer()?;
+        match &mut self.target {
+            Target::Context => self.context.write_trailer()?,
+            Target::Segments(segmenter) => segmenter.close()?,
+            Target::Rtp(rtp) => rtp.close()?,
+        }
+        for tee in &mut self.tees {
+            tee.close()?;
+        }
         Ok(())
     }
 }
 
+/// Where `Muxer::mux` writes the packets.
+enum Target {
+    /// The muxer's own context.
+    Context,
+    /// A series of files, see `MuxerBuilder::segment`.
+    Segments(Segmenter),
+    /// One output per stream, see `MuxerBuilder::rtp`.
+    Rtp(Rtp),
+}
+
+/// The packet of a stream waiting to be written.
+enum Pending {
+    Empty,
+    Packet(Packet),
+    Finished,
+}
+
+/// Write the header of `context`, failing first on options its muxer does not know, so that
+/// no output is started with them.
+fn write_header(context: &mut Context, options: Dictionary) -> Result<(), Error> {
+    context
+        .init_output_with_options(options)?
+        .ensure_consumed()?;
+    context.write_header()
+}
+
+/// Add streams with the codec parameters, time base, metadata and disposition of `template`,
+/// for outputs that mirror the muxer's own.
+fn copy_streams(context: &mut Context, template: &[Stream]) -> Result<Vec<Stream>, Error> {
+    let mut streams = Vec::new();
+    for src in template {
+        let mut stream = Stream::new(context);
+        stream.parameters_from_stream(src)?;
+        stream.set_time_base(src.time_base());
+        stream.set_metadata(&src.metadata())?;
+        stream.disposition = src.disposition;
+        streams.push(stream);
+    }
+    Ok(streams)
+}
+
+impl Muxer {
+    /// Muxer options such as `brand` for MP4. `mux` fails on keys the muxer does not know.
+    pub fn set_header_options(&mut self, options: Dictionary) {
+        self.header_options = options;
+    }
+
+    /// See `MuxerBuilder::max_interleave_delta`.
+    pub fn set_max_interleave_delta(&mut self, value: std::time::Duration) {
+        self.context.set_max_interleave_delta(value);
+    }
+
+    /// SDP describing the streams of an RTP muxer, for the receiver to join them. Connects and
+    /// writes the headers, so add all streams first.
+    pub fn sdp(&mut self) -> Result<String, Error> {
+        match &mut self.target {
+            Target::Rtp(rtp) => {
+                rtp.open(&self.context, core::mem::take(&mut self.header_options))?;
+                rtp.sdp()
+            }
+            _ => Err("SDP is only available for RTP output".into()),
+        }
+    }
+
+    /// The tee outputs dropped under `FailurePolicy::Drop`, by the order they were added in,
+    /// with the error that ended them.
+    pub fn tee_errors(&self) -> Vec<(usize, &Error)> {
+        self.tees
+            .iter()
+            .enumerate()
+            .filter_map(|(index, tee)| Some((index, tee.error()?)))
+            .collect()
+    }
+
+    /// Take back the stream the output was written to, see `MuxerBuilder::open_seekable_writer`.
+    pub fn into_writer<W: 'static>(mut self) -> Option<W> {
+        self.context.take_writer()
+    }
+}
+
+impl Default for Muxer {
+    fn default() -> Self {
+        Self::new(Context::default())
+    }
+}
+
 type CtxWithPktIters = (Context, Vec<PacketIterator>);
 
 impl TryFrom<CtxWithPktIters> for Muxer {
     type Error = Error;
 
     fn try_from((context, pkt_iters): CtxWithPktIters) -> Result<Self, Self::Error> {
-        let context = RefCell::new(context);
-        let streams = pkt_iters
-            .into_iter()
-            .map(|pkt_iter| {
-                let stream = Stream::try_from((&mut *context.borrow_mut(), &*pkt_iter))?;
-                Ok((stream, pkt_iter))
-            })
-            .collect::<Result<Vec<(Stream, PacketIterator)>, Self::Error>>()?;
-
-        Ok(Muxer {
-            context: context.into_inner(),
-            streams,
-        })
+        let mut builder = MuxerBuilder::from(context);
+        for pkt_iter in pkt_iters {
+            builder.add_stream(pkt_iter, StreamOptions::new())?;
+        }
+        Ok(builder.build())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::nd_media::test_util::{encode, encode_h264, encode_silence};
 
     #[test]
     fn should_mux() {
@@ -120,4 +250,295 @@ mod tests {
         named_temp_file.disable_cleanup(false);
         writeln!(std::io::stdout(), "Output file: {}", &url).unwrap();
     }
+
+    #[test]
+    fn should_mux_audio_and_video() {
+        use crate::Encoder;
+        use tempfile::NamedTempFile;
+
+        let video_pkt_iter = encode(&Encoder::video_config(), 60);
+        // 2 seconds of silence.
+        let audio_pkt_iter = encode_silence(100);
+
+        let named_temp_file = NamedTempFile::new().unwrap();
+        let url = format!("file://{}", named_temp_file.path().display());
+
+        let mut context = Context::default();
+        context.open_write(&url).unwrap();
+
+        let mut muxer = Muxer::try_from((context, vec![video_pkt_iter, audio_pkt_iter])).unwrap();
+        muxer.mux().unwrap();
+    }
+
+    #[test]
+    fn should_mux_h264() {
+        use tempfile::NamedTempFile;
+
+        let video_pkt_iter = encode_h264(60);
+
+        let named_temp_file = NamedTempFile::new().unwrap();
+        let url = format!("file://{}", named_temp_file.path().display());
+
+        let mut context = Context::default();
+        context.open_write(&url).unwrap();
+
+        let mut muxer = Muxer::try_from((context, vec![video_pkt_iter])).unwrap();
+        muxer.mux().unwrap();
+    }
+
+    #[test]
+    fn should_mux_into_memory() {
+        use crate::Encoder;
+        use std::io::Cursor;
+
+        let video_pkt_iter = encode(&Encoder::video_config(), 30);
+
+        let mut context = Context::default();
+        context.open_seekable_writer(Cursor::new(Vec::new())).unwrap();
+        let mut muxer = Muxer::try_from((context, vec![video_pkt_iter])).unwrap();
+        muxer.set_header_options(Dictionary::try_from([("brand", "mp42")]).unwrap());
+        muxer.mux().unwrap();
+
+        let bytes = muxer.into_writer::<Cursor<Vec<u8>>>().unwrap().into_inner();
+        assert_eq!(&bytes[4..12], b"ftypmp42");
+        assert!(bytes.windows(4).any(|window| window == b"moov"));
+    }
+
+    #[test]
+    fn should_reject_unknown_header_options() {
+        use crate::Encoder;
+        use std::io::Cursor;
+
+        let video_pkt_iter = Encoder::try_from(&Encoder::video_config())
+            .unwrap()
+            .into_iter();
+
+        let mut builder = Muxer::builder(Format::Mp4)
+            .unwrap()
+            .header_options(Dictionary::try_from([("not_an_option", "1")]).unwrap())
+            .open_seekable_writer(Cursor::new(Vec::new()))
+            .unwrap();
+        builder
+            .add_stream(video_pkt_iter, StreamOptions::new())
+            .unwrap();
+        let mut muxer = builder.build();
+        let err = muxer.mux().unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::UnusedOptions(keys)) if keys == &["not_an_option"]
+        ));
+
+        // Nothing was written with the rejected options.
+        let bytes = muxer.into_writer::<Cursor<Vec<u8>>>().unwrap().into_inner();
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn should_stop_when_interrupted() {
+        use crate::{Encoder, Interrupt};
+        use std::io::Cursor;
+
+        // Never finishes on its own.
+        let video_pkt_iter = encode(&Encoder::video_config(), usize::MAX);
+
+        let mut context = Context::default();
+        let interrupt = Interrupt::new();
+        interrupt.set_timeout(std::time::Duration::from_millis(200));
+        context.set_interrupt(interrupt);
+        context.open_seekable_writer(Cursor::new(Vec::new())).unwrap();
+        let mut muxer = Muxer::try_from((context, vec![video_pkt_iter])).unwrap();
+
+        let err = muxer.mux().unwrap_err();
+        assert!(err.downcast_ref::<Error>().unwrap().is_interrupted());
+    }
+
+    #[test]
+    fn should_abort_blocked_write() {
+        use crate::{Encoder, Interrupt};
+        use std::net::TcpListener;
+
+        // Accepts, but never reads, so the send buffer fills and writing blocks.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("tcp://{}", listener.local_addr().unwrap());
+        let receiver = std::thread::spawn(move || listener.accept().unwrap().0);
+
+        let mut video_config = Encoder::video_config();
+        video_config.change_codec_to_h264();
+        let video_pkt_iter = Encoder::try_from(&video_config).unwrap().into_iter();
+        let video_frame_data_sender = video_pkt_iter.sender();
+        let frame_size = video_config.pixel_count() * 3 / 2;
+
+        // Noise barely compresses, so the buffers fill quickly.
+        std::thread::spawn(move || {
+            let mut seed = 1u32;
+            loop {
+                let data = (0..frame_size)
+                    .map(|_| {
+                        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                        (seed >> 16) as u8
+                    })
+                    .collect();
+                if video_frame_data_sender.send(Some(data)).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let interrupt = Interrupt::new();
+        interrupt.set_timeout(std::time::Duration::from_secs(2));
+        let mut builder = Muxer::builder(Format::MpegTs)
+            .unwrap()
+            .interrupt(interrupt)
+            .open(&url)
+            .unwrap();
+        builder
+            .add_stream(video_pkt_iter, StreamOptions::new())
+            .unwrap();
+
+        let err = builder.build().mux().unwrap_err();
+        assert!(err.downcast_ref::<Error>().unwrap().is_interrupted());
+        drop(receiver.join().unwrap());
+    }
+
+    #[test]
+    fn should_build() {
+        use crate::format::Disposition;
+        use crate::{Demuxer, Encoder};
+        use tempfile::NamedTempFile;
+
+        let video_pkt_iter = encode(&Encoder::video_config(), 30);
+        let audio_pkt_iter = encode_silence(50);
+
+        let named_temp_file = NamedTempFile::new().unwrap();
+        let url = format!("file://{}", named_temp_file.path().display());
+
+        let mut builder = Muxer::builder(Format::Mp4).unwrap().open(&url).unwrap();
+        let video = builder
+            .add_stream(video_pkt_iter, StreamOptions::new().time_base([1, 90000]))
+            .unwrap();
+        let audio = builder
+            .add_stream(
+                audio_pkt_iter,
+                StreamOptions::new()
+                    .metadata(Dictionary::try_from([("language", "jpn")]).unwrap())
+                    .disposition(Disposition::Default),
+            )
+            .unwrap();
+        assert_eq!((video.index(), audio.index()), (0, 1));
+        builder.build().mux().unwrap();
+
+        let demuxer = Demuxer::open(&url).unwrap();
+        let streams = demuxer.streams();
+        assert_eq!(streams.len(), 2);
+        assert_eq!(streams[1].metadata().get("language").as_deref(), Some("jpn"));
+        assert!(streams[1].has_disposition(Disposition::Default));
+    }
+
+    #[test]
+    fn should_interleave_by_dts() {
+        use crate::Demuxer;
+        use std::io::Cursor;
+        use std::time::Duration;
+
+        // x264 with B-frames and lookahead holds back many frames before the first packet.
+        let video_pkt_iter = encode_h264(60);
+        let audio_pkt_iter = encode_silence(100);
+
+        let mut builder = Muxer::builder(Format::Mp4)
+            .unwrap()
+            // FFmpeg's own queue would sort the packets anyway, so pass them on as written.
+            .max_interleave_delta(Duration::from_micros(1))
+            .open_seekable_writer(Cursor::new(Vec::new()))
+            .unwrap();
+        builder.add_stream(video_pkt_iter, StreamOptions::new()).unwrap();
+        builder.add_stream(audio_pkt_iter, StreamOptions::new()).unwrap();
+        let mut muxer = builder.build();
+        muxer.mux().unwrap();
+        let bytes = muxer.into_writer::<Cursor<Vec<u8>>>().unwrap().into_inner();
+
+        let demuxer = Demuxer::from_seekable_reader(Cursor::new(bytes)).unwrap();
+        let time_bases: Vec<_> = demuxer.streams().iter().map(Stream::time_base).collect();
+        let mut packets = demuxer.collect::<Result<Vec<Packet>, _>>().unwrap();
+        // MP4 stores the samples in the order they were written.
+        packets.sort_by_key(|pkt| pkt.pos);
+        assert!(packets.iter().any(|pkt| pkt.stream_index() == 0));
+        assert!(packets.iter().any(|pkt| pkt.stream_index() == 1));
+        let seconds: Vec<f64> = packets
+            .iter()
+            .map(|pkt| {
+                let time_base = time_bases[usize::from(pkt.stream_index())];
+                pkt.dts as f64 * time_base.num as f64 / time_base.den as f64
+            })
+            .collect();
+        // Up to the encoder delay the edit list moves the video by.
+        for pair in seconds.windows(2) {
+            assert!(pair[1] > pair[0] - 0.1, "{:?}", seconds);
+        }
+    }
+
+    #[test]
+    fn should_reject_unsupported_codec() {
+        use crate::Encoder;
+
+        let mut video_config = Encoder::video_config();
+        video_config.change_codec_to_h264();
+        let video_pkt_iter = Encoder::try_from(&video_config).unwrap().into_iter();
+
+        let mut builder = Muxer::builder(Format::WebM).unwrap();
+        match builder.add_stream(video_pkt_iter, StreamOptions::new()) {
+            Err(Error::UnsupportedCodec {
+                codec,
+                format,
+                supported,
+            }) => {
+                assert_eq!((codec.as_str(), format.as_str()), ("h264", "webm"));
+                assert!(supported.iter().any(|name| name == "av1"));
+            }
+            _ => panic!("H.264 accepted in WebM"),
+        }
+    }
+
+    #[test]
+    fn should_write_fragments() {
+        use crate::Encoder;
+        use crate::nd_media::Demuxer;
+        use std::cell::RefCell;
+        use std::io::Cursor;
+        use std::rc::Rc;
+        use std::time::Duration;
+
+        let video_pkt_iter = encode(&Encoder::video_config(), 60);
+
+        let fragments = Rc::new(RefCell::new(Vec::new()));
+        let sink = fragments.clone();
+        // Only the first frame is a keyframe, so fragments are cut by duration.
+        let options = FragmentOptions::new().duration(Duration::from_millis(500));
+        let mut builder = MuxerBuilder::fragmented_mp4(options, move |fragment| {
+            sink.borrow_mut().push(fragment);
+            Ok(())
+        })
+        .unwrap();
+        builder
+            .add_stream(video_pkt_iter, StreamOptions::new())
+            .unwrap();
+        builder.build().mux().unwrap();
+
+        let fragments = fragments.take();
+        let Fragment::Init(init) = &fragments[0] else {
+            panic!("No init segment");
+        };
+        assert_eq!(&init[4..8], b"ftyp");
+        assert!(init.windows(4).any(|window| window == b"moov"));
+        assert!(fragments.len() > 2);
+        for fragment in &fragments[1..] {
+            let Fragment::Media(data) = fragment else {
+                panic!("Second init segment");
+            };
+            assert!(data.windows(4).any(|window| window == b"moof"));
+        }
+
+        let bytes: Vec<u8> = fragments.iter().flat_map(|f| f.data().to_vec()).collect();
+        let demuxer = Demuxer::from_reader(Cursor::new(bytes)).unwrap();
+        assert_eq!(demuxer.count(), 60);
+    }
 }
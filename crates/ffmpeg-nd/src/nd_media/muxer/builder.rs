@@ -0,0 +1,253 @@
+use super::fragment::{Fragment, FragmentOptions, FragmentWriter};
+use super::live::{LiveOptions, LiveWriter};
+use super::packaging::{Packaging, PackagingOptions};
+use super::rtp::Rtp;
+use super::segmenter::{SegmentOptions, Segmenter};
+use super::source::{Packets, Source};
+use super::stream_options::StreamOptions;
+use super::tee::{FailurePolicy, Tee};
+use super::{Muxer, Target};
+use crate::codec::Packet;
+use crate::format::{Context, Format, Interrupt, Output, Stream};
+use crate::nd_media::demuxer::Demuxer;
+use crate::nd_media::packet_iterator::PacketIterator;
+use crate::util::{Dictionary, Error, MediaType};
+use std::io::{Seek, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// Index of a stream added to a `MuxerBuilder`, in the order the streams were added.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StreamHandle(usize);
+
+impl StreamHandle {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Assembles a `Muxer` one output stream at a time.
+pub struct MuxerBuilder {
+    context: Context,
+    streams: Vec<(Stream, Source)>,
+    header_options: Dictionary,
+    target: Target,
+    tees: Vec<Tee>,
+}
+
+impl MuxerBuilder {
+    pub fn new(format: Format) -> Result<Self, Error> {
+        Ok(Self::from(Context::try_from(format)?))
+    }
+
+    /// Write the output to `url` in the format its extension implies, e.g. Matroska for `.mkv`.
+    pub fn from_url<U: AsRef<str>>(url: U) -> Result<Self, Error> {
+        let Some(output) = Output::from_url(&url) else {
+            return Err(format!("Could not guess the output format of '{}'", url.as_ref()).into());
+        };
+        Self::from(Context::try_from(output)?).open(url)
+    }
+
+    /// Fragmented MP4 (CMAF style) for live and progressive delivery. `sink` receives the init
+    /// segment once, then every fragment as soon as the muxer has written it. Calling
+    /// `header_options` afterwards replaces the fragmentation options.
+    pub fn fragmented_mp4<F>(options: FragmentOptions, sink: F) -> Result<Self, Error>
+    where
+        F: FnMut(Fragment) -> std::io::Result<()> + 'static,
+    {
+        Self::new(Format::Mp4)?
+            .header_options(options.header_options()?)
+            .open_writer(FragmentWriter::new(sink))
+    }
+
+    /// Live MPEG-TS to `url`, e.g. `udp://239.0.0.1:1234`, `tcp://host:port` or, with the
+    /// `srt` feature, `srt://host:port`. Connects at once; every packet is sent as soon as it
+    /// is muxed.
+    pub fn live<U: AsRef<str>>(url: U, options: LiveOptions) -> Result<Self, Error> {
+        let header_options = options.header_options()?;
+        Self::new(Format::MpegTs)?
+            .header_options(header_options)
+            .open_writer(LiveWriter::connect(url, options)?)
+    }
+
+    /// RTP, each stream to the URL of the same index in `urls`, e.g. `rtp://host:5004` for
+    /// video and `rtp://host:5006` for audio. Get the SDP for the receiver from `Muxer::sdp`.
+    pub fn rtp<U: AsRef<str>>(urls: &[U]) -> Result<Self, Error> {
+        let Some(output) = Output::from_name("rtp") else {
+            return Err("No rtp muxer".into());
+        };
+        let mut builder = Self::from(Context::try_from(output)?);
+        let urls = urls.iter().map(|url| url.as_ref().to_string()).collect();
+        builder.target = Target::Rtp(Rtp::new(urls));
+        Ok(builder)
+    }
+
+    /// HLS or DASH into `dir`, which is created if needed: a manifest named by
+    /// `Packaging::manifest_name` and the segment files next to it. Do not call `open`.
+    pub fn package<P: AsRef<Path>>(
+        dir: P,
+        packaging: Packaging,
+        options: PackagingOptions,
+    ) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+        let Some(output) = Output::from_name(packaging.short_name()) else {
+            return Err(format!("No {} muxer", packaging.short_name()).into());
+        };
+        let mut context = Context::try_from(output)?;
+        context.set_url(dir.join(packaging.manifest_name()).to_string_lossy());
+        Ok(Self::from(context).header_options(options.header_options(packaging, dir)?))
+    }
+
+    /// Write the output to `url`, e.g. `file:///tmp/out.mp4`.
+    pub fn open<U: AsRef<str>>(mut self, url: U) -> Result<Self, Error> {
+        self.context.open_write(url)?;
+        Ok(self)
+    }
+
+    /// Like `open`, with protocol options. Fails on options the protocol does not know.
+    pub fn open_with_options<U: AsRef<str>>(
+        mut self,
+        url: U,
+        options: Dictionary,
+    ) -> Result<Self, Error> {
+        self.context
+            .open_write_with_options(url, options)?
+            .ensure_consumed()?;
+        Ok(self)
+    }
+
+    /// Write the output to a Rust stream that cannot seek, e.g. a pipe.
+    pub fn open_writer<W: Write + 'static>(mut self, writer: W) -> Result<Self, Error> {
+        self.context.open_writer(writer)?;
+        Ok(self)
+    }
+
+    /// Write the output to a seekable Rust stream, e.g. a `Cursor<Vec<u8>>`.
+    pub fn open_seekable_writer<W: Write + Seek + 'static>(
+        mut self,
+        writer: W,
+    ) -> Result<Self, Error> {
+        self.context.open_seekable_writer(writer)?;
+        Ok(self)
+    }
+
+    /// Muxer options such as `brand` for MP4. `Muxer::mux` fails on keys the muxer does not know.
+    pub fn header_options(mut self, value: Dictionary) -> Self {
+        self.header_options = value;
+        self
+    }
+
+    /// Record to a series of files, each with its own header and trailer, rotated as
+    /// `options` says. Do not call `open`; `options` names the files.
+    pub fn segment(mut self, options: SegmentOptions) -> Self {
+        self.target = Target::Segments(Segmenter::new(options));
+        self
+    }
+
+    /// Also write every packet to `output`, an opened builder with its own format and
+    /// destination, e.g. MPEG-TS over TCP next to an MP4 file. `output` gets the streams of
+    /// this builder, so it fails if streams were added to `output`.
+    pub fn tee(mut self, output: MuxerBuilder, policy: FailurePolicy) -> Result<Self, Error> {
+        if !output.streams.is_empty() {
+            return Err("Streams of a tee output come from the builder it is added to".into());
+        }
+        let tee = Tee::new(output.context, output.header_options, policy);
+        self.tees.push(tee);
+        Ok(self)
+    }
+
+    /// How far apart in time the streams may drift in FFmpeg's own interleaving queue. It does
+    /// not apply to `Muxer::mux`, which always waits for the next packet of every stream.
+    pub fn max_interleave_delta(mut self, value: Duration) -> Self {
+        self.context.set_max_interleave_delta(value);
+        self
+    }
+
+    /// Abort opening and muxing once `interrupt` fires. Set it before `open`.
+    pub fn interrupt(mut self, value: Interrupt) -> Self {
+        self.context.set_interrupt(value);
+        self
+    }
+
+    /// Add a stream fed by an encoder. Fails with `Error::UnsupportedCodec` if the container
+    /// cannot store the codec, e.g. H.264 in WebM.
+    pub fn add_stream(
+        &mut self,
+        pkt_iter: PacketIterator,
+        options: StreamOptions,
+    ) -> Result<StreamHandle, Error> {
+        if let Some(output) = self.context.output() {
+            output.check_codec(&pkt_iter.codec_id.into(), pkt_iter.codec_type())?;
+        }
+        let mut stream = Stream::try_from((&mut self.context, &*pkt_iter))?;
+        options.apply(&mut stream)?;
+        Ok(self.push(stream, Source::Encoded(pkt_iter)))
+    }
+
+    /// Add a stream whose packets are copied without re-encoding from `input`, a stream of
+    /// another container. `packets` must only yield packets of that stream.
+    pub fn add_copied_stream<I>(
+        &mut self,
+        input: &Stream,
+        packets: I,
+        options: StreamOptions,
+    ) -> Result<StreamHandle, Error>
+    where
+        I: Iterator<Item = Result<Packet, Error>> + 'static,
+    {
+        if let Some(output) = self.context.output() {
+            output.check_codec(&input.codec_id().into(), input.codec_type())?;
+        }
+        let mut stream = Stream::new(&mut self.context);
+        stream.parameters_from_stream(input)?;
+        stream.set_time_base(input.time_base());
+        options.apply(&mut stream)?;
+        let packets: Packets = Box::new(packets);
+        let source = Source::Copied {
+            packets,
+            time_base: input.time_base(),
+        };
+        Ok(self.push(stream, source))
+    }
+
+    /// Copy the video, audio and subtitle streams of `demuxer`, e.g. to change the container
+    /// without re-encoding. Other streams such as attachments are skipped.
+    pub fn add_copied_streams(&mut self, demuxer: Demuxer) -> Result<Vec<StreamHandle>, Error> {
+        let mut handles = Vec::new();
+        for (input, packets) in demuxer.into_streams() {
+            if let MediaType::Video | MediaType::Audio | MediaType::Subtitle = input.codec_type() {
+                handles.push(self.add_copied_stream(&input, packets, StreamOptions::new())?);
+            }
+        }
+        Ok(handles)
+    }
+
+    fn push(&mut self, stream: Stream, source: Source) -> StreamHandle {
+        self.streams.push((stream, source));
+        StreamHandle(self.streams.len() - 1)
+    }
+
+    pub fn build(self) -> Muxer {
+        Muxer {
+            context: self.context,
+            streams: self.streams,
+            header_options: self.header_options,
+            target: self.target,
+            tees: self.tees,
+        }
+    }
+}
+
+impl From<Context> for MuxerBuilder {
+    fn from(context: Context) -> Self {
+        MuxerBuilder {
+            context,
+            streams: Vec::new(),
+            header_options: Dictionary::new(),
+            target: Target::Context,
+            tees: Vec::new(),
+        }
+    }
+}
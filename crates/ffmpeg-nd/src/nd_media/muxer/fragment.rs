@@ -0,0 +1,201 @@
+use crate::util::{Dictionary, Error};
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// A piece of fragmented MP4 output, ready to be stored or uploaded on its own.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Fragment {
+    /// `ftyp` and `moov`: the codec setup a player needs before any media fragment.
+    Init(Vec<u8>),
+    /// `moof` and `mdat`: a run of samples, starting on a keyframe unless cut by duration.
+    Media(Vec<u8>),
+}
+
+impl Fragment {
+    pub fn data(&self) -> &[u8] {
+        match self {
+            Fragment::Init(data) | Fragment::Media(data) => data,
+        }
+    }
+}
+
+/// Settings for `MuxerBuilder::fragmented_mp4`.
+pub struct FragmentOptions {
+    duration: Option<Duration>,
+    keyframe: bool,
+}
+
+impl Default for FragmentOptions {
+    fn default() -> Self {
+        FragmentOptions {
+            duration: None,
+            keyframe: true,
+        }
+    }
+}
+
+impl FragmentOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Close a fragment once it holds this much media, even without a keyframe.
+    pub fn duration(mut self, value: Duration) -> Self {
+        self.duration = Some(value);
+        self
+    }
+
+    /// Start a new fragment on every video keyframe. On by default.
+    pub fn keyframe(mut self, value: bool) -> Self {
+        self.keyframe = value;
+        self
+    }
+
+    /// Options of the MP4 muxer. No `mfra` index is written, so the output ends with the
+    /// last fragment.
+    pub(crate) fn header_options(&self) -> Result<Dictionary, Error> {
+        let mut movflags = vec!["empty_moov", "default_base_moof", "skip_trailer"];
+        if self.keyframe {
+            movflags.insert(0, "frag_keyframe");
+        }
+        let mut options = vec![("movflags".to_string(), movflags.join("+"))];
+        if let Some(duration) = self.duration {
+            options.push(("frag_duration".into(), duration.as_micros().to_string()));
+        }
+        // Hand each fragment to the writer as soon as it is written, not when the buffer fills.
+        options.push(("flush_packets".into(), "1".into()));
+        Dictionary::try_from(options)
+    }
+}
+
+/// Splits the byte stream of the MP4 muxer at top-level box boundaries and passes whole
+/// fragments to `sink`.
+pub(crate) struct FragmentWriter<F> {
+    sink: F,
+    buffer: Vec<u8>,
+    /// Length of the complete boxes at the start of `buffer`.
+    complete: usize,
+    init_sent: bool,
+}
+
+impl<F: FnMut(Fragment) -> io::Result<()>> FragmentWriter<F> {
+    pub(crate) fn new(sink: F) -> Self {
+        FragmentWriter {
+            sink,
+            buffer: Vec::new(),
+            complete: 0,
+            init_sent: false,
+        }
+    }
+
+    fn split(&mut self) -> io::Result<()> {
+        while let Some((box_type, size)) = box_header(&self.buffer[self.complete..])? {
+            if self.buffer.len() - self.complete < size {
+                return Ok(());
+            }
+            self.complete += size;
+            let last_box = match self.init_sent {
+                false => b"moov",
+                true => b"mdat",
+            };
+            if &box_type != last_box {
+                continue;
+            }
+            let data = self.buffer.drain(..self.complete).collect();
+            self.complete = 0;
+            let fragment = match self.init_sent {
+                false => Fragment::Init(data),
+                true => Fragment::Media(data),
+            };
+            self.init_sent = true;
+            (self.sink)(fragment)?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: FnMut(Fragment) -> io::Result<()>> Write for FragmentWriter<F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        self.split()?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Type and total size of the box at the start of `data`, or `None` if the header is not
+/// complete yet.
+fn box_header(data: &[u8]) -> io::Result<Option<([u8; 4], usize)>> {
+    if data.len() < 8 {
+        return Ok(None);
+    }
+    let box_type = data[4..8].try_into().unwrap();
+    let size = match u32::from_be_bytes(data[0..4].try_into().unwrap()) {
+        // 64-bit size after the type.
+        1 if data.len() < 16 => return Ok(None),
+        1 => u64::from_be_bytes(data[8..16].try_into().unwrap()),
+        size => size.into(),
+    };
+    // A size of 0 means "to the end of the file", which fragmented output never uses.
+    if size < 8 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid MP4 box size {}", size),
+        ));
+    }
+    Ok(Some((box_type, size as usize)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        fn mp4_box(box_type: &[u8; 4], len: usize) -> Vec<u8> {
+            let mut data = ((len + 8) as u32).to_be_bytes().to_vec();
+            data.extend_from_slice(box_type);
+            data.extend(std::iter::repeat_n(0u8, len));
+            data
+        }
+
+        let stream = [
+            mp4_box(b"ftyp", 16),
+            mp4_box(b"moov", 100),
+            mp4_box(b"moof", 40),
+            mp4_box(b"mdat", 1000),
+            mp4_box(b"moof", 40),
+            mp4_box(b"mdat", 10),
+        ]
+        .concat();
+
+        let mut fragments = Vec::new();
+        let mut writer = FragmentWriter::new(|fragment| {
+            fragments.push(fragment);
+            Ok(())
+        });
+        // Chunks that cut through box headers.
+        for chunk in stream.chunks(7) {
+            writer.write_all(chunk).unwrap();
+        }
+        drop(writer);
+
+        assert_eq!(fragments.len(), 3);
+        assert_eq!(fragments[0], Fragment::Init(stream[..132].to_vec()));
+        assert_eq!(fragments[1], Fragment::Media(stream[132..1180].to_vec()));
+        assert_eq!(fragments[2].data(), &stream[1180..]);
+
+        let options = FragmentOptions::new()
+            .duration(Duration::from_millis(500))
+            .header_options()
+            .unwrap();
+        assert_eq!(
+            options.get("movflags").unwrap(),
+            "frag_keyframe+empty_moov+default_base_moof+skip_trailer"
+        );
+        assert_eq!(options.get("frag_duration").unwrap(), "500000");
+    }
+}
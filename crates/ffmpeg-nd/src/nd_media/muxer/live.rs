@@ -0,0 +1,256 @@
+use crate::format::{Flag, IOContext, Interrupt};
+use crate::util::{Dictionary, Error};
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Bytes of an MPEG-TS packet. Writes to the network end on packet boundaries, so that a
+/// receiver can pick up the stream after a reconnect.
+const TS_PACKET_SIZE: usize = 188;
+
+/// Seven TS packets, which fit the usual 1500 byte MTU.
+const DEFAULT_PKT_SIZE: u32 = 1316;
+
+/// Settings for `MuxerBuilder::live`.
+pub struct LiveOptions {
+    pkt_size: u32,
+    reconnect_attempts: u32,
+    reconnect_delay: Duration,
+    interrupt: Option<Interrupt>,
+    protocol_options: Dictionary,
+}
+
+impl Default for LiveOptions {
+    fn default() -> Self {
+        LiveOptions {
+            pkt_size: DEFAULT_PKT_SIZE,
+            reconnect_attempts: 0,
+            reconnect_delay: Duration::from_secs(1),
+            interrupt: None,
+            protocol_options: Dictionary::new(),
+        }
+    }
+}
+
+impl LiveOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Largest datagram for UDP and SRT, 1316 bytes by default. Keep it a multiple of 188.
+    pub fn pkt_size(mut self, value: u32) -> Self {
+        self.pkt_size = value;
+        self
+    }
+
+    /// When a write fails, connect again up to `attempts` times, `delay` apart, and go on
+    /// with the next packets. Without it a failed write fails `Muxer::mux`.
+    pub fn reconnect(mut self, attempts: u32, delay: Duration) -> Self {
+        self.reconnect_attempts = attempts;
+        self.reconnect_delay = delay;
+        self
+    }
+
+    /// Abort connecting and writing once `interrupt` fires.
+    pub fn interrupt(mut self, value: Interrupt) -> Self {
+        self.interrupt = Some(value);
+        self
+    }
+
+    /// More options of the protocol, e.g. `latency` for SRT. Connecting fails on options the
+    /// protocol does not know.
+    pub fn protocol_options(mut self, value: Dictionary) -> Self {
+        self.protocol_options = value;
+        self
+    }
+
+    /// Options of the `mpegts` muxer: no mux delay, and every packet written out at once.
+    pub(crate) fn header_options(&self) -> Result<Dictionary, Error> {
+        Dictionary::try_from([("flush_packets", "1"), ("max_delay", "0")])
+    }
+}
+
+/// Sends the output of the MPEG-TS muxer to a URL, connecting again when a write fails.
+pub(crate) struct LiveWriter {
+    url: String,
+    options: Dictionary,
+    interrupt: Option<Interrupt>,
+    reconnect_attempts: u32,
+    reconnect_delay: Duration,
+    context: Option<IOContext>,
+    /// The start of a TS packet whose rest has not been written by the muxer yet.
+    pending: Vec<u8>,
+}
+
+impl LiveWriter {
+    pub(crate) fn connect<U: AsRef<str>>(url: U, options: LiveOptions) -> Result<Self, Error> {
+        let url = url.as_ref().to_string();
+        let mut protocol_options = options.protocol_options;
+        match url.split_once("://").map(|(scheme, _)| scheme) {
+            Some("udp") | Some("srt") => {
+                protocol_options.set("pkt_size", options.pkt_size.to_string())?
+            }
+            Some("tcp") => protocol_options.set("tcp_nodelay", "1")?,
+            _ => {}
+        }
+        let mut writer = LiveWriter {
+            url,
+            options: protocol_options,
+            interrupt: options.interrupt,
+            reconnect_attempts: options.reconnect_attempts,
+            reconnect_delay: options.reconnect_delay,
+            context: None,
+            pending: Vec::new(),
+        };
+        writer.open()?;
+        Ok(writer)
+    }
+
+    fn open(&mut self) -> Result<(), Error> {
+        // Close the broken connection first, e.g. to free its port.
+        self.context = None;
+        let int_cb = self
+            .interrupt
+            .as_ref()
+            .map(|interrupt| interrupt.as_callback());
+        let int_cb = int_cb
+            .as_ref()
+            .map_or(core::ptr::null(), |int_cb| int_cb as *const _);
+        let mut context = IOContext::from(core::ptr::null_mut());
+        context
+            .open(&self.url, Flag::Write, int_cb, self.options.clone())?
+            .ensure_consumed()?;
+        self.context = Some(context);
+        Ok(())
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Error> {
+        let mut result = match &mut self.context {
+            Some(context) => context.write_and_flush(data),
+            None => Err("Not connected".into()),
+        };
+        for _ in 0..self.reconnect_attempts {
+            match &result {
+                Ok(()) | Err(Error::Interrupted) => break,
+                Err(_) => {}
+            }
+            std::thread::sleep(self.reconnect_delay);
+            result = self.open().and_then(|()| match &mut self.context {
+                Some(context) => context.write_and_flush(data),
+                None => Ok(()),
+            });
+        }
+        result
+    }
+}
+
+impl Write for LiveWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        let len = self.pending.len() / TS_PACKET_SIZE * TS_PACKET_SIZE;
+        if len > 0 {
+            let data: Vec<u8> = self.pending.drain(..len).collect();
+            self.send(&data).map_err(io::Error::other)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Demuxer;
+    use crate::nd_media::muxer::{MuxerBuilder, StreamOptions};
+    use crate::nd_media::test_util::encode_h264;
+    use std::io::{Cursor, Read};
+    use std::net::{TcpListener, UdpSocket};
+
+    /// Encode `frames` frames of H.264 and send them live to `url`.
+    fn stream(url: &str, options: LiveOptions, frames: usize) {
+        let mut builder = MuxerBuilder::live(url, options).unwrap();
+        builder
+            .add_stream(encode_h264(frames), StreamOptions::new())
+            .unwrap();
+        // Dropping the muxer closes the connection.
+        builder.build().mux().unwrap();
+    }
+
+    fn count_packets(bytes: Vec<u8>) -> usize {
+        Demuxer::from_reader(Cursor::new(bytes)).unwrap().count()
+    }
+
+    #[test]
+    fn should_stream_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("tcp://{}", listener.local_addr().unwrap());
+        let receiver = std::thread::spawn(move || {
+            let mut bytes = Vec::new();
+            listener
+                .accept()
+                .unwrap()
+                .0
+                .read_to_end(&mut bytes)
+                .unwrap();
+            bytes
+        });
+
+        stream(&url, LiveOptions::new(), 30);
+        let bytes = receiver.join().unwrap();
+        assert_eq!(bytes.len() % TS_PACKET_SIZE, 0);
+        assert_eq!(count_packets(bytes), 30);
+    }
+
+    #[test]
+    fn should_stream_over_udp() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let url = format!("udp://{}", socket.local_addr().unwrap());
+        let receiver = std::thread::spawn(move || {
+            let mut datagrams = Vec::new();
+            let mut buf = [0u8; 65536];
+            socket
+                .set_read_timeout(Some(Duration::from_secs(2)))
+                .unwrap();
+            while let Ok(len) = socket.recv(&mut buf) {
+                datagrams.push(buf[..len].to_vec());
+            }
+            datagrams
+        });
+
+        stream(&url, LiveOptions::new(), 30);
+        let datagrams = receiver.join().unwrap();
+        assert!(!datagrams.is_empty());
+        for datagram in &datagrams {
+            assert!(datagram.len() <= DEFAULT_PKT_SIZE as usize);
+            assert_eq!(datagram.len() % TS_PACKET_SIZE, 0);
+        }
+        assert!(count_packets(datagrams.concat()) > 0);
+    }
+
+    #[test]
+    fn should_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("tcp://{}", listener.local_addr().unwrap());
+        let receiver = std::thread::spawn(move || {
+            // The first receiver goes away at once.
+            drop(listener.accept().unwrap());
+            let mut bytes = Vec::new();
+            listener
+                .accept()
+                .unwrap()
+                .0
+                .read_to_end(&mut bytes)
+                .unwrap();
+            bytes
+        });
+
+        let options = LiveOptions::new().reconnect(5, Duration::from_millis(100));
+        stream(&url, options, 90);
+        // The stream goes on from a packet boundary on the new connection.
+        let bytes = receiver.join().unwrap();
+        assert_eq!(bytes.len() % TS_PACKET_SIZE, 0);
+        assert!(count_packets(bytes) > 0);
+    }
+}
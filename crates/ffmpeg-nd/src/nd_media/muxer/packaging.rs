@@ -0,0 +1,237 @@
+use crate::util::{Dictionary, Error};
+use std::path::Path;
+use std::time::Duration;
+
+/// Adaptive streaming formats, written as a manifest plus segment files into one directory.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Packaging {
+    /// `index.m3u8` with MPEG-TS or fragmented MP4 segments.
+    Hls,
+    /// `manifest.mpd` with fragmented MP4 segments.
+    Dash,
+}
+
+impl Packaging {
+    pub(crate) fn short_name(&self) -> &'static str {
+        match self {
+            Packaging::Hls => "hls",
+            Packaging::Dash => "dash",
+        }
+    }
+
+    /// File name of the manifest inside the output directory.
+    pub fn manifest_name(&self) -> &'static str {
+        match self {
+            Packaging::Hls => "index.m3u8",
+            Packaging::Dash => "manifest.mpd",
+        }
+    }
+}
+
+/// Whether the playlist is final or may still grow.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub enum PlaylistType {
+    #[default]
+    Vod,
+    /// Segments are only ever appended, e.g. for a live event that can be rewound.
+    Event,
+}
+
+/// Container of HLS segments. DASH segments are always fragmented MP4.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub enum SegmentFormat {
+    #[default]
+    MpegTs,
+    Mp4,
+}
+
+/// Settings for `MuxerBuilder::package`.
+pub struct PackagingOptions {
+    segment_duration: Duration,
+    playlist_type: PlaylistType,
+    segment_format: SegmentFormat,
+    segment_prefix: String,
+}
+
+impl Default for PackagingOptions {
+    fn default() -> Self {
+        PackagingOptions {
+            segment_duration: Duration::from_secs(4),
+            playlist_type: PlaylistType::default(),
+            segment_format: SegmentFormat::default(),
+            segment_prefix: "segment_".into(),
+        }
+    }
+}
+
+impl PackagingOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Target length of a segment. Segments only start on keyframes, so they last at least
+    /// this long, rounded up to the next keyframe. 4 seconds by default.
+    pub fn segment_duration(mut self, value: Duration) -> Self {
+        self.segment_duration = value;
+        self
+    }
+
+    /// HLS only. A DASH manifest is dynamic while it is written and static once muxing ends.
+    pub fn playlist_type(mut self, value: PlaylistType) -> Self {
+        self.playlist_type = value;
+        self
+    }
+
+    /// HLS only.
+    pub fn segment_format(mut self, value: SegmentFormat) -> Self {
+        self.segment_format = value;
+        self
+    }
+
+    /// Start of the segment file names, followed by a sequence number, e.g. `segment_00003.ts`.
+    pub fn segment_prefix<S: AsRef<str>>(mut self, value: S) -> Self {
+        self.segment_prefix = value.as_ref().to_string();
+        self
+    }
+
+    /// Options of the `hls` or `dash` muxer writing into `dir`. Fails if the prefix or `dir`
+    /// contains a NUL byte.
+    pub(crate) fn header_options(
+        &self,
+        packaging: Packaging,
+        dir: &Path,
+    ) -> Result<Dictionary, Error> {
+        let duration = self.segment_duration.as_secs_f64().to_string();
+        let prefix = &self.segment_prefix;
+        match packaging {
+            Packaging::Hls => {
+                let (segment_type, extension) = match self.segment_format {
+                    SegmentFormat::MpegTs => ("mpegts", "ts"),
+                    SegmentFormat::Mp4 => ("fmp4", "m4s"),
+                };
+                let playlist_type = match self.playlist_type {
+                    PlaylistType::Vod => "vod",
+                    PlaylistType::Event => "event",
+                };
+                // Segment paths are relative to the working directory, the init segment's to
+                // the playlist.
+                let segments = dir.join(format!("{}%05d.{}", prefix, extension));
+                Dictionary::try_from([
+                    ("hls_time", duration),
+                    ("hls_list_size", "0".into()),
+                    ("hls_playlist_type", playlist_type.into()),
+                    ("hls_segment_type", segment_type.into()),
+                    ("hls_segment_filename", segments.to_string_lossy().into()),
+                    ("hls_fmp4_init_filename", format!("{}init.mp4", prefix)),
+                ])
+            }
+            // Segment names are relative to the manifest.
+            Packaging::Dash => Dictionary::try_from([
+                ("seg_duration", duration),
+                ("dash_segment_type", "mp4".into()),
+                ("use_template", "1".into()),
+                ("use_timeline", "1".into()),
+                (
+                    "init_seg_name",
+                    format!("{}init_$RepresentationID$.$ext$", prefix),
+                ),
+                (
+                    "media_seg_name",
+                    format!("{}$RepresentationID$_$Number%05d$.$ext$", prefix),
+                ),
+            ]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Demuxer;
+    use crate::nd_media::muxer::MuxerBuilder;
+    use crate::nd_media::test_util::mp4_with_keyframes;
+    use std::fs;
+    use std::io::Cursor;
+
+    /// Copy five seconds of H.264 with its default two second GOP into `builder`, from an MP4.
+    /// Returns the time of each keyframe of the MP4 in seconds from the first.
+    fn mux(mut builder: MuxerBuilder) -> Vec<f64> {
+        let bytes = mp4_with_keyframes(5);
+
+        let demuxer = Demuxer::from_seekable_reader(Cursor::new(bytes.clone())).unwrap();
+        let time_base = demuxer.stream(0).unwrap().time_base();
+        let keyframes: Vec<f64> = demuxer
+            .map(Result::unwrap)
+            .filter(|pkt| pkt.is_key())
+            .map(|pkt| pkt.pts as f64 * time_base.num as f64 / time_base.den as f64)
+            .collect();
+
+        let demuxer = Demuxer::from_seekable_reader(Cursor::new(bytes)).unwrap();
+        builder.add_copied_streams(demuxer).unwrap();
+        builder.build().mux().unwrap();
+        keyframes.iter().map(|time| time - keyframes[0]).collect()
+    }
+
+    #[test]
+    fn should_package_hls() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = PackagingOptions::new()
+            .segment_duration(Duration::from_secs(1))
+            .segment_prefix("part");
+        let keyframes = mux(MuxerBuilder::package(dir.path(), Packaging::Hls, options).unwrap());
+
+        let playlist = fs::read_to_string(dir.path().join("index.m3u8")).unwrap();
+        assert!(playlist.contains("#EXT-X-PLAYLIST-TYPE:VOD"));
+        assert!(playlist.contains("#EXT-X-ENDLIST"));
+
+        let durations: Vec<f64> = playlist
+            .lines()
+            .filter_map(|line| line.strip_prefix("#EXTINF:"))
+            .map(|line| line.trim_end_matches(',').parse().unwrap())
+            .collect();
+        assert_eq!(durations.iter().sum::<f64>().round(), 5.0);
+        // Segments are cut at the first keyframe after one second. The GOP is longer, so
+        // every keyframe starts a segment.
+        let starts: Vec<f64> = durations
+            .iter()
+            .scan(0.0, |start, duration| {
+                let current = *start;
+                *start += duration;
+                Some(current)
+            })
+            .collect();
+        let matches = starts.len() == keyframes.len()
+            && (starts.iter().zip(&keyframes)).all(|(start, key)| (start - key).abs() < 0.001);
+        assert!(matches, "{:?} {:?}", starts, keyframes);
+
+        let segments: Vec<&str> = playlist
+            .lines()
+            .filter(|line| line.ends_with(".ts"))
+            .collect();
+        assert_eq!(segments.len(), durations.len());
+        for segment in segments {
+            assert!(segment.starts_with("part"));
+            assert!(dir.path().join(segment).is_file());
+        }
+    }
+
+    #[test]
+    fn should_package_dash() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = PackagingOptions::new().segment_duration(Duration::from_secs(1));
+        mux(MuxerBuilder::package(dir.path(), Packaging::Dash, options).unwrap());
+
+        let manifest = fs::read_to_string(dir.path().join("manifest.mpd")).unwrap();
+        assert!(manifest.contains("type=\"static\""));
+        assert!(manifest.contains("segment_init_$RepresentationID$"));
+        assert!(dir.path().join("segment_init_0.m4s").is_file());
+        assert!(dir.path().join("segment_0_00001.m4s").is_file());
+    }
+
+    #[test]
+    fn should_reject_nul_in_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = PackagingOptions::new().segment_prefix("part\0");
+        assert!(MuxerBuilder::package(dir.path(), Packaging::Hls, options).is_err());
+    }
+}
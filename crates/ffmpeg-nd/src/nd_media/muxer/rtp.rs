@@ -0,0 +1,129 @@
+use super::{copy_streams, write_header};
+use crate::codec::Packet;
+use crate::format::{Context, Output, Stream};
+use crate::util::{Dictionary, Error};
+
+/// Sends each stream of a `Muxer` to its own `rtp://` URL, as the `rtp` muxer takes a single
+/// stream. The streams of the muxer's own context serve as the template of each output.
+pub(crate) struct Rtp {
+    urls: Vec<String>,
+    contexts: Vec<Context>,
+    streams: Vec<Stream>,
+}
+
+impl Rtp {
+    pub(crate) fn new(urls: Vec<String>) -> Self {
+        Rtp {
+            urls,
+            contexts: Vec::new(),
+            streams: Vec::new(),
+        }
+    }
+
+    /// Connect and write the headers, unless already done.
+    pub(crate) fn open(&mut self, template: &Context, options: Dictionary) -> Result<(), Error> {
+        if !self.contexts.is_empty() {
+            return Ok(());
+        }
+        let streams = template.streams();
+        if streams.len() != self.urls.len() {
+            return Err(
+                format!("{} RTP URLs for {} streams", self.urls.len(), streams.len()).into(),
+            );
+        }
+        let Some(output) = Output::from_name("rtp") else {
+            return Err("No rtp muxer".into());
+        };
+        // Kept only once every output is open, so that a failed open can be retried.
+        let (mut contexts, mut copies) = (Vec::new(), Vec::new());
+        for (stream, url) in streams.iter().zip(&self.urls) {
+            let mut context = Context::try_from(Output::from(output.as_ptr()))?;
+            if let Some(interrupt) = template.interrupt() {
+                context.set_interrupt(interrupt);
+            }
+            let mut copy = copy_streams(&mut context, core::slice::from_ref(stream))?;
+            // The SDP takes the destination from the URL of the context.
+            context.set_url(url);
+            context.open_write(url)?;
+            write_header(&mut context, options.clone())?;
+            copies.append(&mut copy);
+            contexts.push(context);
+        }
+        self.contexts = contexts;
+        self.streams = copies;
+        Ok(())
+    }
+
+    pub(crate) fn sdp(&mut self) -> Result<String, Error> {
+        Context::sdp(&mut self.contexts)
+    }
+
+    /// Write `pkt`, in the time base of its stream in `template`, to the output of its stream.
+    pub(crate) fn write(&mut self, template: &Context, mut pkt: Packet) -> Result<(), Error> {
+        let index = usize::from(pkt.stream_index());
+        let Some(stream) = template.stream(index) else {
+            return Err(format!("No stream {}", index).into());
+        };
+        pkt.rescale_ts(&stream.time_base(), &self.streams[index].time_base());
+        pkt.set_stream_index(0);
+        self.contexts[index].interleaved_write_frame(&mut pkt)
+    }
+
+    pub(crate) fn close(&mut self) -> Result<(), Error> {
+        for context in &mut self.contexts {
+            context.write_trailer()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nd_media::muxer::{MuxerBuilder, StreamOptions};
+    use crate::nd_media::test_util::encode_h264;
+    use crate::{Format, Muxer};
+    use std::net::UdpSocket;
+    use std::time::Duration;
+
+    #[test]
+    fn test() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = socket.local_addr().unwrap().port();
+        let receiver = std::thread::spawn(move || {
+            let mut datagrams = Vec::new();
+            let mut buf = [0u8; 65536];
+            socket
+                .set_read_timeout(Some(Duration::from_secs(2)))
+                .unwrap();
+            while let Ok(len) = socket.recv(&mut buf) {
+                datagrams.push(buf[..len].to_vec());
+            }
+            datagrams
+        });
+
+        let mut builder = MuxerBuilder::rtp(&[format!("rtp://127.0.0.1:{}", port)]).unwrap();
+        builder
+            .add_stream(encode_h264(30), StreamOptions::new())
+            .unwrap();
+        let mut muxer = builder.build();
+        let sdp = muxer.sdp().unwrap();
+        assert!(
+            sdp.contains(&format!("m=video {} RTP/AVP 96", port)),
+            "{}",
+            sdp
+        );
+        assert!(sdp.contains("a=rtpmap:96 H264/90000"), "{}", sdp);
+        muxer.mux().unwrap();
+
+        let datagrams = receiver.join().unwrap();
+        assert!(!datagrams.is_empty());
+        for datagram in &datagrams {
+            // RTP version 2 with the dynamic payload type of the SDP.
+            assert_eq!(datagram[0] >> 6, 2);
+            assert_eq!(datagram[1] & 0x7f, 96);
+        }
+
+        // Not an RTP muxer.
+        assert!(Muxer::builder(Format::Mp4).unwrap().build().sdp().is_err());
+    }
+}
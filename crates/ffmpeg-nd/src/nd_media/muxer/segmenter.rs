@@ -0,0 +1,317 @@
+use super::{copy_streams, write_header};
+use crate::codec::Packet;
+use crate::format::{Context, Stream};
+use crate::util::{AV_NOPTS_VALUE, Dictionary, Error, MediaType, Rational, rescale};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MICROSECONDS: Rational = Rational {
+    num: 1,
+    den: 1_000_000,
+};
+
+/// Settings for `MuxerBuilder::segment`: when to start a new file and what to call it.
+pub struct SegmentOptions {
+    template: String,
+    duration: Option<Duration>,
+    size: Option<u64>,
+    on_close: Option<Box<dyn FnMut(&Segment)>>,
+}
+
+impl SegmentOptions {
+    /// `template` names the files: `{index}` is replaced by the segment number, from 0, and
+    /// `{timestamp}` by the Unix time in seconds the segment was opened at, e.g.
+    /// `file:///rec/cam_{timestamp}_{index}.mp4`.
+    pub fn new<S: AsRef<str>>(template: S) -> Self {
+        SegmentOptions {
+            template: template.as_ref().to_string(),
+            duration: None,
+            size: None,
+            on_close: None,
+        }
+    }
+
+    /// Start a new segment at the first keyframe once the current one lasts this long.
+    pub fn duration(mut self, value: Duration) -> Self {
+        self.duration = Some(value);
+        self
+    }
+
+    /// Start a new segment at the first keyframe once the current one has this many bytes.
+    pub fn size(mut self, bytes: u64) -> Self {
+        self.size = Some(bytes);
+        self
+    }
+
+    /// Called after the trailer of a segment is written, e.g. to upload the file.
+    pub fn on_close<F: FnMut(&Segment) + 'static>(mut self, callback: F) -> Self {
+        self.on_close = Some(Box::new(callback));
+        self
+    }
+
+    fn url(&self, index: usize) -> String {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.template
+            .replace("{index}", &index.to_string())
+            .replace("{timestamp}", &timestamp.to_string())
+    }
+}
+
+/// A closed segment, passed to `SegmentOptions::on_close`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Segment {
+    index: usize,
+    url: String,
+    start: Duration,
+    duration: Duration,
+    size: u64,
+}
+
+impl Segment {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Position of the first packet in the whole recording.
+    pub fn start(&self) -> Duration {
+        self.start
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Size of the file in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Writes the packets of a `Muxer` to a series of files, each with its own header and
+/// trailer. The streams of the muxer's own context serve as the template of each file.
+pub(crate) struct Segmenter {
+    options: SegmentOptions,
+    header_options: Dictionary,
+    current: Option<Current>,
+    index: usize,
+    /// Start of the first segment in microseconds.
+    origin: Option<i64>,
+}
+
+/// The open segment.
+struct Current {
+    context: Context,
+    streams: Vec<Stream>,
+    url: String,
+    /// Start and end in microseconds; timestamps in the file are relative to the start.
+    start: i64,
+    end: i64,
+}
+
+impl Segmenter {
+    pub(crate) fn new(options: SegmentOptions) -> Self {
+        Segmenter {
+            options,
+            header_options: Dictionary::new(),
+            current: None,
+            index: 0,
+            origin: None,
+        }
+    }
+
+    /// Muxer options for the header of every segment.
+    pub(crate) fn set_header_options(&mut self, options: Dictionary) {
+        self.header_options = options;
+    }
+
+    /// Write `pkt`, in the time base of its stream in `template`, first starting a new segment
+    /// if `pkt` is a keyframe and the current one is long or large enough.
+    pub(crate) fn write(&mut self, template: &Context, mut pkt: Packet) -> Result<(), Error> {
+        let streams = template.streams();
+        let index = usize::from(pkt.stream_index());
+        let time_base = streams[index].time_base();
+        let ts = rescale(pkt.dts_or_pts(), time_base, MICROSECONDS);
+
+        // Segments are cut on the video, or on the first stream if there is none.
+        let cut_stream = streams
+            .iter()
+            .find(|stream| stream.codec_type() == MediaType::Video)
+            .or(streams.first())
+            .map(|stream| usize::from(stream.index()));
+        let rotate = match &self.current {
+            None => true,
+            Some(current) if pkt.is_key() && cut_stream == Some(index) => {
+                let elapsed = Duration::from_micros((ts - current.start).max(0) as u64);
+                self.options.duration.is_some_and(|value| elapsed >= value)
+                    || self
+                        .options
+                        .size
+                        .is_some_and(|value| current.context.bytes_written() >= value)
+            }
+            Some(_) => false,
+        };
+        if rotate {
+            self.close()?;
+            self.open(template, &streams, ts)?;
+        }
+
+        let Some(current) = self.current.as_mut() else {
+            return Ok(());
+        };
+        let end = ts + rescale(pkt.duration, time_base, MICROSECONDS);
+        current.end = current.end.max(end);
+        let offset = rescale(current.start, MICROSECONDS, time_base);
+        if pkt.pts != AV_NOPTS_VALUE {
+            pkt.pts -= offset;
+        }
+        if pkt.dts != AV_NOPTS_VALUE {
+            pkt.dts -= offset;
+        }
+        pkt.rescale_ts(&time_base, &current.streams[index].time_base());
+        current.context.interleaved_write_frame(&mut pkt)
+    }
+
+    fn open(&mut self, template: &Context, streams: &[Stream], start: i64) -> Result<(), Error> {
+        let Some(output) = template.output() else {
+            return Err("Segmenting needs an output format".into());
+        };
+        let mut context = Context::try_from(output)?;
+        if let Some(interrupt) = template.interrupt() {
+            context.set_interrupt(interrupt);
+        }
+        let copies = copy_streams(&mut context, streams)?;
+
+        let url = self.options.url(self.index);
+        context.open_write(&url)?;
+        write_header(&mut context, self.header_options.clone())?;
+        self.origin.get_or_insert(start);
+        self.current = Some(Current {
+            context,
+            streams: copies,
+            url,
+            start,
+            end: start,
+        });
+        Ok(())
+    }
+
+    /// Write the trailer of the open segment, if any, and report it to `on_close`.
+    pub(crate) fn close(&mut self) -> Result<(), Error> {
+        let Some(mut current) = self.current.take() else {
+            return Ok(());
+        };
+        current.context.write_trailer()?;
+        let segment = Segment {
+            index: self.index,
+            start: Duration::from_micros((current.start - self.origin.unwrap_or(0)).max(0) as u64),
+            duration: Duration::from_micros((current.end - current.start).max(0) as u64),
+            size: current.context.bytes_written(),
+            url: current.url,
+        };
+        drop(current.context);
+        self.index += 1;
+        if let Some(on_close) = self.options.on_close.as_mut() {
+            on_close(&segment);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nd_media::muxer::Muxer;
+    use crate::nd_media::test_util::mp4_with_keyframes;
+    use crate::{Demuxer, Format};
+    use std::cell::RefCell;
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    /// Record five seconds of H.264 with its default two second GOP, copied from an MP4.
+    /// Returns the closed segments and the time of each keyframe of the MP4 from its first
+    /// packet, measured like `Segment::start`.
+    fn record(options: SegmentOptions) -> (Vec<Segment>, Vec<Duration>) {
+        let segments = Rc::new(RefCell::new(Vec::new()));
+        let closed = segments.clone();
+        let options = options.on_close(move |segment| closed.borrow_mut().push(segment.clone()));
+
+        let bytes = mp4_with_keyframes(5);
+
+        let demuxer = Demuxer::from_seekable_reader(Cursor::new(bytes.clone())).unwrap();
+        let time_base = demuxer.stream(0).unwrap().time_base();
+        let packets: Vec<Packet> = demuxer.map(Result::unwrap).collect();
+        let origin = packets[0].dts_or_pts();
+        let keyframes = packets
+            .iter()
+            .filter(|pkt| pkt.is_key())
+            .map(|pkt| rescale(pkt.dts_or_pts() - origin, time_base, MICROSECONDS))
+            .map(|ts| Duration::from_micros(ts as u64))
+            .collect();
+
+        let mut builder = Muxer::builder(Format::Mp4).unwrap().segment(options);
+        let demuxer = Demuxer::from_seekable_reader(Cursor::new(bytes)).unwrap();
+        builder.add_copied_streams(demuxer).unwrap();
+        builder.build().mux().unwrap();
+        (segments.take(), keyframes)
+    }
+
+    /// Assert that `actual` are the `expected` times, give or take rounding.
+    fn assert_times(actual: &[Duration], expected: &[Duration]) {
+        let close = |(a, b): (&Duration, &Duration)| a.abs_diff(*b) < Duration::from_millis(1);
+        let same = actual.len() == expected.len() && actual.iter().zip(expected).all(close);
+        assert!(same, "{:?} {:?}", actual, expected);
+    }
+
+    #[test]
+    fn should_rotate_by_duration() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = format!(
+            "file://{}/rec_{{index}}_{{timestamp}}.mp4",
+            dir.path().display()
+        );
+        let options = SegmentOptions::new(template).duration(Duration::from_secs(1));
+        let (segments, keyframes) = record(options);
+
+        // New segments start at the first keyframe after one second.
+        let mut starts = vec![keyframes[0]];
+        for &keyframe in &keyframes {
+            if keyframe - *starts.last().unwrap() >= Duration::from_secs(1) {
+                starts.push(keyframe);
+            }
+        }
+        assert!(starts.len() > 1);
+        let segment_starts: Vec<Duration> = segments.iter().map(Segment::start).collect();
+        assert_times(&segment_starts, &starts);
+        for (index, segment) in segments.iter().enumerate() {
+            assert_eq!(segment.index(), index);
+            assert!(segment.url().contains(&format!("rec_{}_", index)));
+            assert!(segment.size() > 0);
+        }
+        assert_times(&[segments[0].duration()], &[starts[1] - starts[0]]);
+
+        for segment in &segments {
+            let demuxer = Demuxer::open(segment.url()).unwrap();
+            let mut packets = demuxer.map(|pkt| pkt.unwrap()).peekable();
+            assert!(packets.peek().unwrap().is_key());
+            assert!(packets.count() > 1);
+        }
+    }
+
+    #[test]
+    fn should_rotate_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = format!("file://{}/rec_{{index}}.mp4", dir.path().display());
+        // Any segment is large enough, so every keyframe starts a new one.
+        let (segments, keyframes) = record(SegmentOptions::new(template).size(1));
+        let segment_starts: Vec<Duration> = segments.iter().map(Segment::start).collect();
+        assert_times(&segment_starts, &keyframes);
+        let last = segments.len() - 1;
+        assert!(segments[last].url().ends_with(&format!("rec_{}.mp4", last)));
+    }
+}
@@ -0,0 +1,48 @@
+use crate::codec::Packet;
+use crate::nd_media::packet_iterator::PacketIterator;
+use crate::util::{Error, Rational};
+use std::rc::Rc;
+
+pub(crate) type Packets = Box<dyn Iterator<Item = Result<Packet, Error>>>;
+
+/// Where the packets of one output stream come from.
+pub(crate) enum Source {
+    /// Packets produced by an encoder.
+    Encoded(PacketIterator),
+    /// Packets taken as they are from another container.
+    Copied {
+        packets: Packets,
+        time_base: Rational,
+    },
+}
+
+impl Source {
+    pub fn time_base(&self) -> Rational {
+        match self {
+            Source::Encoded(pkt_iter) => pkt_iter.time_base(),
+            Source::Copied { time_base, .. } => *time_base,
+        }
+    }
+}
+
+impl Iterator for Source {
+    type Item = Result<Packet, Box<dyn std::error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Source::Encoded(pkt_iter) => Some(
+                pkt_iter
+                    .next()?
+                    .map(|pkt| Rc::try_unwrap(pkt).unwrap_or_else(|pkt| (*pkt).clone())),
+            ),
+            Source::Copied { packets, .. } => Some(match packets.next()? {
+                Ok(mut pkt) => {
+                    // The byte position in the input means nothing in the output.
+                    pkt.pos = -1;
+                    Ok(pkt)
+                }
+                Err(e) => Err(e.into()),
+            }),
+        }
+    }
+}
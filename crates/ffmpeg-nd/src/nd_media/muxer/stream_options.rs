@@ -0,0 +1,42 @@
+use crate::format::{Disposition, Stream};
+use crate::util::{Dictionary, Error, Rational};
+
+/// Per-stream settings applied when a stream is added to a `MuxerBuilder`.
+#[derive(Default)]
+pub struct StreamOptions {
+    metadata: Dictionary,
+    dispositions: Vec<Disposition>,
+    time_base: Option<Rational>,
+}
+
+impl StreamOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Entries such as `language=eng` or `title`.
+    pub fn metadata(mut self, value: Dictionary) -> Self {
+        self.metadata = value;
+        self
+    }
+
+    pub fn disposition(mut self, value: Disposition) -> Self {
+        self.dispositions.push(value);
+        self
+    }
+
+    /// A hint; the muxer may still choose its own time base.
+    pub fn time_base<R: Into<Rational>>(mut self, value: R) -> Self {
+        self.time_base = Some(value.into());
+        self
+    }
+
+    pub(crate) fn apply(&self, stream: &mut Stream) -> Result<(), Error> {
+        stream.set_metadata(&self.metadata)?;
+        stream.set_disposition(&self.dispositions);
+        if let Some(time_base) = self.time_base {
+            stream.set_time_base(time_base);
+        }
+        Ok(())
+    }
+}
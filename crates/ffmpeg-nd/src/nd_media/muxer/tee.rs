@@ -0,0 +1,182 @@
+use super::{copy_streams, write_header};
+use crate::codec::Packet;
+use crate::format::{Context, Stream};
+use crate::util::{Dictionary, Error};
+
+/// What `Muxer::mux` does when writing to a tee output fails.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub enum FailurePolicy {
+    /// Stop muxing and return the error.
+    #[default]
+    Abort,
+    /// Stop writing to this output only, e.g. a network endpoint next to an archive file.
+    Drop,
+}
+
+/// An extra output receiving a copy of every packet the muxer writes.
+pub(crate) struct Tee {
+    context: Context,
+    header_options: Dictionary,
+    policy: FailurePolicy,
+    streams: Vec<Stream>,
+    /// Why the output was dropped.
+    error: Option<Error>,
+}
+
+impl Tee {
+    pub(crate) fn new(context: Context, header_options: Dictionary, policy: FailurePolicy) -> Self {
+        Tee {
+            context,
+            header_options,
+            policy,
+            streams: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub(crate) fn error(&self) -> Option<&Error> {
+        self.error.as_ref()
+    }
+
+    /// Add the streams of `template` and write the header.
+    pub(crate) fn open(&mut self, template: &Context) -> Result<(), Error> {
+        self.guard(|tee| {
+            if let Some(output) = tee.context.output() {
+                for stream in template.streams() {
+                    output.check_codec(&stream.codec_id().into(), stream.codec_type())?;
+                }
+            }
+            tee.streams = copy_streams(&mut tee.context, &template.streams())?;
+            let options = core::mem::take(&mut tee.header_options);
+            write_header(&mut tee.context, options)
+        })
+    }
+
+    /// Write a copy of `pkt`, which is in the time base of its stream in `template`.
+    pub(crate) fn write(&mut self, template: &Context, pkt: &Packet) -> Result<(), Error> {
+        self.guard(|tee| {
+            let index = usize::from(pkt.stream_index());
+            let Some(stream) = template.stream(index) else {
+                return Err(format!("No stream {}", index).into());
+            };
+            let mut pkt = pkt.clone();
+            pkt.rescale_ts(&stream.time_base(), &tee.streams[index].time_base());
+            pkt.set_stream_index(tee.streams[index].index());
+            tee.context.interleaved_write_frame(&mut pkt)
+        })
+    }
+
+    pub(crate) fn close(&mut self) -> Result<(), Error> {
+        self.guard(|tee| tee.context.write_trailer())
+    }
+
+    /// Run `f` unless the output was dropped, applying the failure policy to its error.
+    fn guard<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut Self) -> Result<(), Error>,
+    {
+        if self.error.is_some() {
+            return Ok(());
+        }
+        match (f(self), self.policy) {
+            (Err(e), FailurePolicy::Drop) => {
+                self.error = Some(e);
+                Ok(())
+            }
+            (result, _) => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nd_media::muxer::{Muxer, MuxerBuilder, StreamOptions};
+    use crate::nd_media::test_util::encode_h264;
+    use crate::{Demuxer, Encoder, Format};
+    use std::io::{self, Write};
+
+    /// A destination that goes away, like a closed connection.
+    struct Broken;
+
+    impl Write for Broken {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("connection lost"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Encode 30 frames of H.264 into `builder`.
+    fn mux(mut builder: MuxerBuilder) -> (Muxer, Result<(), Box<dyn std::error::Error>>) {
+        builder
+            .add_stream(encode_h264(30), StreamOptions::new())
+            .unwrap();
+        let mut muxer = builder.build();
+        let result = muxer.mux();
+        (muxer, result)
+    }
+
+    #[test]
+    fn should_write_to_all_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let mp4 = format!("file://{}/out.mp4", dir.path().display());
+        let ts = format!("file://{}/out.ts", dir.path().display());
+
+        let broken = Muxer::builder(Format::MpegTs)
+            .unwrap()
+            .open_writer(Broken)
+            .unwrap();
+        let builder = MuxerBuilder::from_url(&mp4)
+            .unwrap()
+            .tee(MuxerBuilder::from_url(&ts).unwrap(), FailurePolicy::Abort)
+            .unwrap()
+            .tee(broken, FailurePolicy::Drop)
+            .unwrap();
+        let (muxer, result) = mux(builder);
+        result.unwrap();
+
+        let errors = muxer.tee_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 1);
+
+        for url in [mp4, ts] {
+            let demuxer = Demuxer::open(&url).unwrap();
+            assert_eq!(demuxer.streams()[0].codec_name(), "h264");
+            assert_eq!(demuxer.count(), 30);
+        }
+    }
+
+    #[test]
+    fn should_abort_on_failed_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let mp4 = format!("file://{}/out.mp4", dir.path().display());
+
+        let broken = Muxer::builder(Format::MpegTs)
+            .unwrap()
+            .open_writer(Broken)
+            .unwrap();
+        let builder = MuxerBuilder::from_url(&mp4)
+            .unwrap()
+            .tee(broken, FailurePolicy::Abort)
+            .unwrap();
+        let (_, result) = mux(builder);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_reject_output_with_streams() {
+        let mut output = Muxer::builder(Format::MpegTs)
+            .unwrap()
+            .open_writer(Broken)
+            .unwrap();
+        let pkt_iter = Encoder::try_from(&Encoder::video_config())
+            .unwrap()
+            .into_iter();
+        output.add_stream(pkt_iter, StreamOptions::new()).unwrap();
+        let builder = Muxer::builder(Format::Mp4).unwrap();
+        assert!(builder.tee(output, FailurePolicy::Abort).is_err());
+    }
+}
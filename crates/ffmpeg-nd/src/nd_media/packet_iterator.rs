@@ -1,116 +1,250 @@
-use super::frame_data::FrameData;
-use crate::codec::{Context, Packet};
-use crate::util::{Frame, MediaType, PixelFormat};
+use super::frame_data::{FrameData, InputFormat};
+use super::image_sender::ImageSender;
+use super::sample_buffer::SampleBuffer;
+use crate::codec::{Codec, Context, Packet};
+#[cfg(feature = "swresample")]
+use crate::swresample::Resampler;
+#[cfg(feature = "swresample")]
+use crate::util::{ChannelLayout, SampleFormat};
+#[cfg(feature = "swscale")]
+use crate::swscale::{Interpolation, Scaler};
+use crate::util::{Error, Frame, MediaType};
 use core::iter::Iterator;
 use core::ops::Deref;
+use ffi::*;
 use kanal;
+use std::collections::VecDeque;
 use std::rc::Rc;
 
 pub struct PacketIterator {
     context: Context,
-    packet: Rc<Packet>,
-    internal_packet_chan: (
-        kanal::Sender<Option<Rc<Packet>>>,
-        kanal::Receiver<Option<Rc<Packet>>>,
-    ),
+    packets: VecDeque<Packet>,
     frame: Frame,
     frame_data_chan: (
         kanal::Sender<Option<Vec<u8>>>,
         kanal::Receiver<Option<Vec<u8>>>,
     ),
+    /// Video only: layout of the data sent, if it is not the encoder's pixel format.
+    input_format: Option<InputFormat>,
+    /// Video only: scales frames sent at another size to the encoder's, with the frame they are written to.
+    #[cfg(feature = "swscale")]
+    scaler: Option<(Scaler, Frame)>,
+    /// Audio only: converts PCM sent in another format to the encoder's.
+    #[cfg(feature = "swresample")]
+    resampler: Option<Resampler>,
+    /// Audio only: PCM not yet sent to the encoder.
+    samples: Option<SampleBuffer>,
     eof: bool,
     pts: u32,
 }
 
 impl PacketIterator {
+    /// Video takes one whole frame per message. Audio takes PCM in the encoder's sample format
+    /// in any number of samples; planar data carries the planes of all channels back to back.
     pub fn sender(&self) -> kanal::Sender<Option<Vec<u8>>> {
         self.frame_data_chan.0.clone()
     }
 
+    /// Accept packed RGB frames from `sender()` and convert them to the encoder's pixel format.
+    pub fn set_input_format(&mut self, value: InputFormat) {
+        self.input_format = Some(value);
+    }
+
+    /// Accept frames of `width` x `height` from `sender()` and scale them to the encoder's resolution.
+    #[cfg(feature = "swscale")]
+    pub fn set_input_size(
+        &mut self,
+        width: u16,
+        height: u16,
+        interpolation: Interpolation,
+    ) -> Result<(), Error> {
+        let pix_fmt = self.context.pix_fmt();
+        let scaler = Scaler::new(
+            (width, height, pix_fmt),
+            (self.context.width(), self.context.height(), pix_fmt),
+            interpolation,
+        )?;
+        let mut input = Frame::new();
+        input.set_format(pix_fmt);
+        input.set_width(width);
+        input.set_height(height);
+        input.set_colorspace(self.context.colorspace());
+        input.set_color_range(self.context.color_range());
+        input.get_buffer()?;
+        self.scaler = Some((scaler, input));
+        Ok(())
+    }
+
+    /// Accept PCM from `sender()` in the given format and resample it to the encoder's.
+    #[cfg(feature = "swresample")]
+    pub fn set_input_audio(
+        &mut self,
+        sample_fmt: SampleFormat,
+        sample_rate: u32,
+        ch_layout: &ChannelLayout,
+    ) -> Result<(), Error> {
+        self.resampler = Some(Resampler::new(
+            (sample_fmt, sample_rate, ch_layout),
+            (
+                self.context.sample_fmt(),
+                self.context.sample_rate(),
+                &self.context.ch_layout(),
+            ),
+        )?);
+        Ok(())
+    }
+
+    /// Send `image` crate images instead of raw frames; switches the input format to RGBA.
+    pub fn image_sender(&mut self) -> ImageSender {
+        self.set_input_format(InputFormat::RGBA);
+        let framerate = self.context.frame_rate();
+        let (width, height) = self.input_size();
+        ImageSender::new(
+            self.sender(),
+            width.into(),
+            height.into(),
+            framerate.num as f64 / framerate.den as f64,
+        )
+    }
+
+    /// Video only: size of the frames expected from `sender()`.
+    fn input_size(&self) -> (u16, u16) {
+        #[cfg(feature = "swscale")]
+        if let Some((_, input)) = &self.scaler {
+            return (input.width(), input.height());
+        }
+        (self.context.width(), self.context.height())
+    }
+
     pub fn next_pts(&self) -> u32 {
         self.pts
     }
 
-    fn frame_data(&self, data: Vec<u8>) -> FrameData {
+    fn frame_data(&mut self, data: Vec<u8>) -> Result<Vec<FrameData>, Error> {
         match self.context.codec_type() {
-            MediaType::Video => match self.context.pix_fmt() {
-                PixelFormat::None => panic!("PixelFormat::None is not supported"),
-                PixelFormat::RGB24 => FrameData::RGB24(data),
-                PixelFormat::YUV444P => FrameData::YUV444P(data),
-                PixelFormat::YUV420P => unimplemented!("YUV420P is not supported yet"),
+            MediaType::Video => match self.input_format {
+                Some(input_format) => Ok(vec![FrameData::Convert(input_format, data)]),
+                None => match FrameData::video(self.context.pix_fmt(), data) {
+                    Some(frame_data) => Ok(vec![frame_data]),
+                    None => Err(format!(
+                        "PixelFormat::{:?} is not supported",
+                        self.context.pix_fmt()
+                    )
+                    .into()),
+                },
             },
-            MediaType::Audio => unimplemented!("Audio is not supported yet"),
+            MediaType::Audio => {
+                #[cfg(feature = "swresample")]
+                let data = match self.resampler.as_mut() {
+                    Some(resampler) => resampler.convert_samples(&data)?,
+                    None => data,
+                };
+                self.buffer_samples(data)
+            }
             _ => unimplemented!("Unsupported media type"),
         }
     }
-}
 
-impl Iterator for PacketIterator {
-    type Item = Result<Rc<Packet>, Box<dyn std::error::Error>>;
+    /// Audio only: queue PCM in the encoder's format and cut off every whole frame.
+    fn buffer_samples(&mut self, data: Vec<u8>) -> Result<Vec<FrameData>, Error> {
+        let frame_size = self.context.samples_per_frame();
+        let samples = self.samples.as_mut().unwrap();
+        samples.push(data)?;
+        Ok(core::iter::from_fn(|| samples.pop(frame_size)).collect())
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.eof {
-            return match self.internal_packet_chan.1.recv() {
-                Ok(Some(packet)) => Some(Ok(packet)),
-                Ok(None) => None,
-                Err(e) => Some(Err(e.into())),
-            };
+    /// Audio only: frames completed by the samples the resampler held back until EOF.
+    #[cfg(feature = "swresample")]
+    fn flush_resampler(&mut self) -> Result<Vec<FrameData>, Error> {
+        match self.resampler.as_mut() {
+            Some(resampler) => {
+                let data = resampler.flush_samples()?;
+                self.buffer_samples(data)
+            }
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Audio samples left over at EOF, padded to a full frame unless the encoder accepts a short one.
+    fn remaining_frame_data(&mut self) -> Option<FrameData> {
+        let codec = Codec::from(self.context.codec);
+        let pad_to = match self.context.frame_size() {
+            0 => None,
+            _ if codec.has_capability(AV_CODEC_CAP_SMALL_LAST_FRAME) => None,
+            n => Some(n as usize),
+        };
+        self.samples.as_mut()?.pop_remaining(pad_to)
+    }
+
+    fn send_frame_data(&mut self, frame_data: FrameData) -> Result<(), Box<dyn std::error::Error>> {
+        self.frame.make_writable()?;
+        #[cfg(feature = "swscale")]
+        if let Some((scaler, input)) = self.scaler.as_mut() {
+            input.make_writable()?;
+            frame_data.set_to_frame(input, &mut self.pts)?;
+            scaler.scale_into(input, &mut self.frame)?;
+        } else {
+            frame_data.set_to_frame(&mut self.frame, &mut self.pts)?;
         }
+        #[cfg(not(feature = "swscale"))]
+        frame_data.set_to_frame(&mut self.frame, &mut self.pts)?;
+        self.context.send_frame(&self.frame)?;
+        Ok(self.receive_packets()?)
+    }
 
-        if let Err(e) = self.internal_packet_chan.0.send(None) {
-            return Some(Err(e.into()));
+    /// Drain every packet the encoder can output right now.
+    fn receive_packets(&mut self) -> Result<(), Error> {
+        loop {
+            let mut packet = Packet::new();
+            match self.context.receive_packet(&mut packet) {
+                Ok(()) => self.packets.push_back(packet),
+                Err(e) if e.is_eagain() => return Ok(()),
+                Err(e) if e.is_eof() => {
+                    self.eof = true;
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
+            }
         }
+    }
+}
 
-        let result = (|| {
+impl Iterator for PacketIterator {
+    type Item = Result<Rc<Packet>, Box<dyn std::error::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result: Result<Option<Rc<Packet>>, Box<dyn std::error::Error>> = (|| {
             loop {
-                if let Some(packet) = self.internal_packet_chan.1.recv()? {
-                    return Ok(packet);
-                };
+                if let Some(packet) = self.packets.pop_front() {
+                    return Ok(Some(Rc::new(packet)));
+                }
+                if self.eof {
+                    return Ok(None);
+                }
 
                 match self.frame_data_chan.1.recv()? {
                     None => {
                         self.frame_data_chan.1.close()?;
+                        #[cfg(feature = "swresample")]
+                        for frame_data in self.flush_resampler()? {
+                            self.send_frame_data(frame_data)?;
+                        }
+                        if let Some(frame_data) = self.remaining_frame_data() {
+                            self.send_frame_data(frame_data)?;
+                        }
                         self.context.send_eof()?;
+                        self.receive_packets()?;
                     }
                     Some(data) => {
-                        self.frame.make_writable()?;
-                        self.frame_data(data)
-                            .set_to_frame(&mut self.frame, &mut self.pts);
-                        eprintln!("Send frame {:?}", self.frame.pts);
-                        self.context.send_frame(&self.frame)?;
+                        for frame_data in self.frame_data(data)? {
+                            self.send_frame_data(frame_data)?;
+                        }
                     }
                 };
-
-                let mut loop_num = -1;
-                loop {
-                    loop_num += 1;
-                    if loop_num > 0 {
-                        self.packet = Rc::new(Packet::new());
-                    }
-
-                    let pkt_mut = Rc::get_mut(&mut self.packet).unwrap();
-                    if let Err(e) = self.context.receive_packet(pkt_mut) {
-                        match e {
-                            e if e.is_eagain() || e.is_eof() => {
-                                if e.is_eof() {
-                                    self.eof = true;
-                                }
-                                self.internal_packet_chan.0.send(None)?;
-                                break;
-                            }
-                            _ => return Err(e.into()),
-                        }
-                    } else {
-                        eprintln!("Received packet pts: {}", self.packet.pts);
-                        self.internal_packet_chan
-                            .0
-                            .send(Some(Rc::clone(&self.packet)))?;
-                    };
-                }
             }
         })();
 
-        Some(result)
+        result.transpose()
     }
 }
 
@@ -126,10 +260,15 @@ impl Default for PacketIterator {
     fn default() -> Self {
         Self {
             context: Context::default(),
-            packet: Rc::new(Packet::new()),
-            internal_packet_chan: kanal::unbounded::<Option<Rc<Packet>>>(),
+            packets: VecDeque::new(),
             frame: Frame::new(),
             frame_data_chan: kanal::bounded::<Option<Vec<u8>>>(8),
+            input_format: None,
+            #[cfg(feature = "swscale")]
+            scaler: None,
+            #[cfg(feature = "swresample")]
+            resampler: None,
+            samples: None,
             eof: false,
             pts: 0,
         }
@@ -146,9 +285,17 @@ impl From<Context> for PacketIterator {
                 Ok(Frame::new())
             })
             .unwrap();
+        let samples = match context.borrow().codec_type() {
+            MediaType::Audio => Some(SampleBuffer::new(
+                context.borrow().sample_fmt(),
+                context.borrow().ch_layout().nb_channels().into(),
+            )),
+            _ => None,
+        };
         PacketIterator {
             context: context.into_inner(),
             frame,
+            samples,
             ..Default::default()
         }
     }
@@ -177,4 +324,119 @@ mod tests {
             let _pkt = result.unwrap();
         }
     }
+
+    #[test]
+    fn test_audio() {
+        use crate::Encoder;
+
+        let config = Encoder::audio_config();
+        let pkt_iter = Encoder::try_from(&config).unwrap().into_iter();
+        let sender = pkt_iter.sender();
+
+        std::thread::spawn(move || {
+            // 1 second of 440 Hz in 480-sample chunks, planar float stereo.
+            for chunk in 0..100 {
+                let plane = (0..480)
+                    .map(|i| {
+                        let t = (chunk * 480 + i) as f32 / 48000.0;
+                        (t * 440.0 * core::f32::consts::TAU).sin() * 0.5
+                    })
+                    .flat_map(f32::to_le_bytes)
+                    .collect::<Vec<u8>>();
+                sender.send(Some([plane.clone(), plane].concat())).unwrap();
+            }
+            sender.send(None).unwrap()
+        });
+
+        let packets = pkt_iter.collect::<Result<Vec<_>, _>>().unwrap();
+        // 48000 samples in 1024-sample frames, plus the encoder delay.
+        assert!(packets.len() >= 47);
+        assert!(packets.windows(2).all(|pair| pair[0].pts < pair[1].pts));
+    }
+
+    #[test]
+    fn test_rgba_input() {
+        use crate::Encoder;
+
+        let mut config = Encoder::video_config();
+        config.change_codec_to_h264();
+        config.change_color_matrix_to_bt709();
+        let mut pkt_iter = Encoder::try_from(&config).unwrap().into_iter();
+        pkt_iter.set_input_format(InputFormat::RGBA);
+        let pixel_count = config.pixel_count();
+        let sender = pkt_iter.sender();
+
+        std::thread::spawn(move || {
+            for i in 0..30u8 {
+                sender.send(Some(vec![i * 8; pixel_count * 4])).unwrap();
+            }
+            sender.send(None).unwrap()
+        });
+
+        assert_eq!(pkt_iter.map(Result::unwrap).count(), 30);
+    }
+
+    #[test]
+    fn test_image_input() {
+        use crate::Encoder;
+
+        let mut pkt_iter = Encoder::try_from(&Encoder::video_config()).unwrap().into_iter();
+        let image_sender = pkt_iter.image_sender();
+
+        std::thread::spawn(move || {
+            for i in 0..30 {
+                let image = image::RgbImage::from_pixel(640, 360, image::Rgb([i * 8, 128, 255]));
+                image_sender.send_rgb_image(&image).unwrap();
+            }
+            image_sender.finish().unwrap()
+        });
+
+        assert_eq!(pkt_iter.map(Result::unwrap).count(), 30);
+    }
+
+    #[cfg(feature = "swscale")]
+    #[test]
+    fn test_input_size() {
+        use crate::Encoder;
+
+        let mut pkt_iter = Encoder::try_from(&Encoder::video_config()).unwrap().into_iter();
+        pkt_iter.set_input_size(640, 480, Interpolation::Bicubic).unwrap();
+        let size = pkt_iter.pix_fmt().buffer_size(640, 480);
+        let sender = pkt_iter.sender();
+
+        std::thread::spawn(move || {
+            for _ in 0..30 {
+                sender.send(Some(vec![128u8; size])).unwrap();
+            }
+            sender.send(None).unwrap()
+        });
+
+        assert_eq!(pkt_iter.map(Result::unwrap).count(), 30);
+    }
+
+    #[cfg(feature = "swresample")]
+    #[test]
+    fn test_input_audio() {
+        use crate::Encoder;
+
+        // AAC takes 48 kHz FLTP; feed it 44.1 kHz interleaved S16.
+        let mut config = Encoder::audio_config();
+        config.change_codec_to_aac();
+        config.change_sample_rate_to_48000();
+        let mut pkt_iter = Encoder::try_from(&config).unwrap().into_iter();
+        pkt_iter
+            .set_input_audio(SampleFormat::S16, 44100, &ChannelLayout::stereo())
+            .unwrap();
+        let sender = pkt_iter.sender();
+
+        std::thread::spawn(move || {
+            for _ in 0..100 {
+                sender.send(Some(vec![0u8; 441 * 4])).unwrap();
+            }
+            sender.send(None).unwrap()
+        });
+
+        let packets = pkt_iter.collect::<Result<Vec<_>, _>>().unwrap();
+        assert!(packets.len() >= 47);
+    }
 }
@@ -0,0 +1,97 @@
+//! Container conversion without re-encoding, like `ffmpeg -i input -c copy output`.
+
+use super::demuxer::Demuxer;
+use super::muxer::{Muxer, MuxerBuilder};
+use crate::format::Format;
+
+/// Copy the video, audio and subtitle streams of `input` into a new container at `output`,
+/// e.g. WebM to Matroska or MP4 to MPEG-TS. The format follows the extension of `output`.
+pub fn remux<I: AsRef<str>, O: AsRef<str>>(
+    input: I,
+    output: O,
+) -> Result<(), Box<dyn std::error::Error>> {
+    copy_streams(Demuxer::open(input)?, MuxerBuilder::from_url(output)?)
+}
+
+/// Like `remux`, writing `format` whatever the extension of `output`.
+pub fn remux_as<I: AsRef<str>, O: AsRef<str>>(
+    input: I,
+    output: O,
+    format: Format,
+) -> Result<(), Box<dyn std::error::Error>> {
+    copy_streams(Demuxer::open(input)?, Muxer::builder(format)?.open(output)?)
+}
+
+fn copy_streams(
+    demuxer: Demuxer,
+    mut builder: MuxerBuilder,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if builder.add_copied_streams(demuxer)?.is_empty() {
+        return Err("No stream to copy".into());
+    }
+    builder.build().mux()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nd_media::test_util::{encode, encode_silence};
+
+    #[test]
+    fn test() {
+        use crate::codec::Packet;
+        use crate::{Encoder, StreamOptions};
+        use tempfile::NamedTempFile;
+
+        let video_pkt_iter = encode(&Encoder::video_config(), 30);
+        let audio_pkt_iter = encode_silence(50);
+
+        // Matroska takes both AV1 and AAC.
+        let input_file = NamedTempFile::new().unwrap();
+        let input = format!("file://{}", input_file.path().display());
+        let mut builder = Muxer::builder(Format::Matroska)
+            .unwrap()
+            .open(&input)
+            .unwrap();
+        builder
+            .add_stream(video_pkt_iter, StreamOptions::new())
+            .unwrap();
+        builder
+            .add_stream(audio_pkt_iter, StreamOptions::new())
+            .unwrap();
+        builder.build().mux().unwrap();
+
+        let output_file = NamedTempFile::new().unwrap();
+        let output = format!("file://{}", output_file.path().display());
+        remux_as(&input, &output, Format::Mp4).unwrap();
+
+        let count = |url: &str| {
+            let demuxer = Demuxer::open(url).unwrap();
+            let streams = demuxer.streams().len();
+            let packets = demuxer.collect::<Result<Vec<Packet>, _>>().unwrap();
+            // The streams stay interleaved rather than one after the other.
+            let first_audio = packets.iter().position(|pkt| pkt.stream_index() == 1);
+            let last_video = packets.iter().rposition(|pkt| pkt.stream_index() == 0);
+            assert!(first_audio < last_video, "{}", url);
+            (0..streams)
+                .map(|index| {
+                    packets
+                        .iter()
+                        .filter(|pkt| pkt.stream_index() as usize == index)
+                        .count()
+                })
+                .collect::<Vec<_>>()
+        };
+        let counts = count(&output);
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts, count(&input));
+        assert_eq!(counts[0], 30);
+        assert!(counts[1] > 0);
+
+        // The format follows the extension.
+        let output_file = tempfile::Builder::new().suffix(".mkv").tempfile().unwrap();
+        let output = format!("file://{}", output_file.path().display());
+        remux(&input, &output).unwrap();
+        assert_eq!(count(&output), counts);
+    }
+}
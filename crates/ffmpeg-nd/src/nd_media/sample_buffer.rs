@@ -0,0 +1,105 @@
+use super::frame_data::FrameData;
+use crate::util::{Error, SampleFormat};
+
+/// Collects PCM sent to an audio `PacketIterator` in arbitrary chunk sizes and
+/// cuts it into frames of the encoder's `frame_size`.
+pub(crate) struct SampleBuffer {
+    planes: Vec<Vec<u8>>,
+    /// Bytes of one sample within one plane.
+    sample_size: usize,
+    planar: bool,
+}
+
+impl SampleBuffer {
+    pub fn new(sample_fmt: SampleFormat, nb_channels: usize) -> Self {
+        let planar = sample_fmt.is_planar();
+        let (nb_planes, sample_size) = if planar {
+            (nb_channels, sample_fmt.bytes_per_sample())
+        } else {
+            (1, sample_fmt.bytes_per_sample() * nb_channels)
+        };
+        SampleBuffer {
+            planes: vec![Vec::new(); nb_planes],
+            sample_size,
+            planar,
+        }
+    }
+
+    /// Append PCM bytes. Planar data holds the planes of all channels back to back.
+    pub fn push(&mut self, data: Vec<u8>) -> Result<(), Error> {
+        let chunk_size = self.sample_size * self.planes.len();
+        if data.len() % chunk_size != 0 {
+            return Err(format!(
+                "PCM data of {} bytes is not a whole number of {}-byte samples",
+                data.len(),
+                chunk_size
+            )
+            .into());
+        }
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let plane_len = data.len() / self.planes.len();
+        for (plane, chunk) in self.planes.iter_mut().zip(data.chunks(plane_len)) {
+            plane.extend_from_slice(chunk);
+        }
+        Ok(())
+    }
+
+    pub fn nb_samples(&self) -> usize {
+        self.planes[0].len() / self.sample_size
+    }
+
+    /// Take exactly `nb_samples` samples, or `None` if fewer are buffered.
+    pub fn pop(&mut self, nb_samples: usize) -> Option<FrameData> {
+        if nb_samples == 0 || self.nb_samples() < nb_samples {
+            return None;
+        }
+
+        let len = nb_samples * self.sample_size;
+        let mut planes: Vec<Vec<u8>> = self
+            .planes
+            .iter_mut()
+            .map(|plane| plane.drain(..len).collect())
+            .collect();
+
+        Some(if self.planar {
+            FrameData::Planar(planes)
+        } else {
+            FrameData::Interleaved(planes.remove(0))
+        })
+    }
+
+    /// Take everything left, padded with silence up to `pad_to` samples if given.
+    pub fn pop_remaining(&mut self, pad_to: Option<usize>) -> Option<FrameData> {
+        if let Some(pad_to) = pad_to {
+            if self.nb_samples() > 0 {
+                for plane in self.planes.iter_mut() {
+                    plane.resize(pad_to * self.sample_size, 0);
+                }
+            }
+        }
+        self.pop(self.nb_samples())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut buffer = SampleBuffer::new(SampleFormat::FLTP, 2);
+        buffer.push(vec![0u8; 2 * 4 * 700]).unwrap();
+        assert!(buffer.pop(1024).is_none());
+        buffer.push(vec![0u8; 2 * 4 * 700]).unwrap();
+        assert!(matches!(buffer.pop(1024), Some(FrameData::Planar(planes)) if planes.len() == 2 && planes[0].len() == 4096));
+        assert_eq!(buffer.nb_samples(), 376);
+        assert!(matches!(buffer.pop_remaining(Some(1024)), Some(FrameData::Planar(planes)) if planes[1].len() == 4096));
+        assert!(buffer.pop_remaining(None).is_none());
+
+        let mut buffer = SampleBuffer::new(SampleFormat::S16, 2);
+        buffer.push(vec![0u8; 3]).unwrap_err();
+    }
+}
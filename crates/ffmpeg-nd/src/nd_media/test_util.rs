@@ -0,0 +1,73 @@
+//! Encoded input for the tests.
+
+use super::encoder::{Encoder, VideoConfig};
+use super::muxer::{Muxer, StreamOptions};
+use super::packet_iterator::PacketIterator;
+use crate::format::Format;
+use std::io::Cursor;
+
+/// Encode `frames` frames with `config`, sent from another thread. Each frame is filled with
+/// its index, so that consecutive frames differ. Sending stops early once the packets are
+/// dropped, so `usize::MAX` gives a stream that only ends with its reader.
+pub(crate) fn encode(config: &VideoConfig, frames: usize) -> PacketIterator {
+    let pkt_iter = Encoder::try_from(config).unwrap().into_iter();
+    let sender = pkt_iter.sender();
+    let planes = pkt_iter
+        .pix_fmt()
+        .planes(pkt_iter.width().into(), pkt_iter.height().into());
+    let frame_size = planes.iter().map(|(row, rows)| row * rows).sum();
+
+    std::thread::spawn(move || {
+        for i in 0..frames {
+            if sender.send(Some(vec![i as u8; frame_size])).is_err() {
+                return;
+            }
+        }
+        let _ = sender.send(None);
+    });
+    pkt_iter
+}
+
+/// Like `encode`, with the default video config switched to H.264.
+pub(crate) fn encode_h264(frames: usize) -> PacketIterator {
+    let mut config = Encoder::video_config();
+    config.change_codec_to_h264();
+    encode(&config, frames)
+}
+
+/// Encode `chunks` times 20 ms of silence with the default audio config, planar float stereo
+/// at 48 kHz.
+pub(crate) fn encode_silence(chunks: usize) -> PacketIterator {
+    let pkt_iter = Encoder::try_from(&Encoder::audio_config())
+        .unwrap()
+        .into_iter();
+    let sender = pkt_iter.sender();
+
+    std::thread::spawn(move || {
+        for _ in 0..chunks {
+            if sender.send(Some(vec![0u8; 960 * 4 * 2])).is_err() {
+                return;
+            }
+        }
+        let _ = sender.send(None);
+    });
+    pkt_iter
+}
+
+/// An MP4 of `secs` seconds of H.264 at 30 fps, with a keyframe every two seconds as by the
+/// default GOP.
+pub(crate) fn mp4_with_keyframes(secs: u32) -> Vec<u8> {
+    let mut config = Encoder::video_config();
+    config.change_codec_to_h264();
+    config.change_framerate_to_30();
+    let pkt_iter = encode(&config, 30 * secs as usize);
+
+    let mut builder = Muxer::builder(Format::Mp4)
+        .unwrap()
+        .open_seekable_writer(Cursor::new(Vec::new()))
+        .unwrap();
+    builder.add_stream(pkt_iter, StreamOptions::new()).unwrap();
+    let mut muxer = builder.build();
+    muxer.mux().unwrap();
+    muxer.into_writer::<Cursor<Vec<u8>>>().unwrap().into_inner()
+}
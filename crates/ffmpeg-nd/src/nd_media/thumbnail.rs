@@ -0,0 +1,183 @@
+//! Decoded frames as `image` crate buffers, and poster frames extracted from files.
+
+use super::decoder::Decoder;
+use super::demuxer::Demuxer;
+use super::frame_data::{FrameDataError, InputFormat, convert_from_frame};
+use crate::util::{Frame, MediaType, Rational, rescale};
+use image::{RgbImage, RgbaImage};
+use std::path::Path;
+use std::time::Duration;
+
+const MICROSECONDS: Rational = Rational {
+    num: 1,
+    den: 1_000_000,
+};
+
+impl TryFrom<&Frame> for RgbImage {
+    type Error = FrameDataError;
+
+    fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+        let data = convert_from_frame(frame, InputFormat::RGB24)?;
+        Ok(RgbImage::from_raw(frame.width().into(), frame.height().into(), data).unwrap())
+    }
+}
+
+impl TryFrom<&Frame> for RgbaImage {
+    type Error = FrameDataError;
+
+    fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+        let data = convert_from_frame(frame, InputFormat::RGBA)?;
+        Ok(RgbaImage::from_raw(frame.width().into(), frame.height().into(), data).unwrap())
+    }
+}
+
+/// Decode the first video frame shown at or after `position` in `url`.
+/// Falls back to the last frame when `position` is past the end.
+pub fn thumbnail<U: AsRef<str>>(
+    url: U,
+    position: Duration,
+) -> Result<RgbImage, Box<dyn std::error::Error>> {
+    let mut demuxer = Demuxer::open(url)?;
+    let Some(stream) = demuxer.best_stream(MediaType::Video) else {
+        return Err("No video stream".into());
+    };
+    let mut decoder = Decoder::try_from(&stream)?;
+
+    // `seek` counts from the start of the input, so the frame to stop at does too.
+    demuxer.seek(position)?;
+    let position = i64::try_from(position.as_micros()).unwrap_or(i64::MAX);
+    let start = demuxer.start_time().unwrap_or(0);
+    let target = rescale(start.saturating_add(position), MICROSECONDS, stream.time_base());
+
+    let mut last: Option<Frame> = None;
+    let mut eof = false;
+    loop {
+        let mut frame = Frame::new();
+        match decoder.receive_frame(&mut frame) {
+            Ok(()) if frame.best_effort_timestamp >= target => return Ok((&frame).try_into()?),
+            Ok(()) => {
+                last = Some(frame);
+                continue;
+            }
+            Err(e) if e.is_eof() => break,
+            Err(e) if e.is_eagain() => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        if eof {
+            break;
+        }
+        match demuxer.next().transpose()? {
+            Some(packet) if packet.stream_index() == stream.index() => decoder.send_packet(&packet)?,
+            Some(_) => {}
+            None => {
+                eof = true;
+                decoder.send_packet_eof()?;
+            }
+        }
+    }
+
+    match last {
+        Some(frame) => Ok((&frame).try_into()?),
+        None => Err("No video frame decoded".into()),
+    }
+}
+
+/// Save the frame at `position` in `url` as an image, e.g. a PNG or JPEG chosen by the extension of `path`.
+pub fn save_thumbnail<U: AsRef<str>, P: AsRef<Path>>(
+    url: U,
+    position: Duration,
+    path: P,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Ok(thumbnail(url, position)?.save(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        use crate::format::Context;
+        use crate::{Encoder, Muxer};
+        use tempfile::{NamedTempFile, TempDir};
+
+        let mut video_pkt_iter = Encoder::try_from(&Encoder::video_config()).unwrap().into_iter();
+        let image_sender = video_pkt_iter.image_sender();
+
+        // One second of gray levels increasing with each frame.
+        std::thread::spawn(move || {
+            for i in 0..60u8 {
+                let image = RgbImage::from_pixel(320, 240, image::Rgb([i * 4; 3]));
+                image_sender.send_rgb_image(&image).unwrap();
+            }
+            image_sender.finish().unwrap()
+        });
+
+        let named_temp_file = NamedTempFile::new().unwrap();
+        let url = format!("file://{}", named_temp_file.path().display());
+        let mut context = Context::default();
+        context.open_write(&url).unwrap();
+        Muxer::try_from((context, vec![video_pkt_iter]))
+            .unwrap()
+            .mux()
+            .unwrap();
+
+        let early = thumbnail(&url, Duration::from_millis(100)).unwrap();
+        let late = thumbnail(&url, Duration::from_millis(800)).unwrap();
+        assert_eq!(early.dimensions(), (320, 240));
+        assert!(early.get_pixel(160, 120)[0] < late.get_pixel(160, 120)[0]);
+
+        let dir = TempDir::new().unwrap();
+        for name in ["poster.png", "poster.jpg"] {
+            let path = dir.path().join(name);
+            save_thumbnail(&url, Duration::from_secs(10), &path).unwrap();
+            assert_eq!(image::open(&path).unwrap().width(), 320);
+        }
+    }
+
+    #[test]
+    fn should_seek_from_start_time() {
+        use crate::format::Format;
+        use crate::{Encoder, Muxer, StreamOptions};
+        use tempfile::NamedTempFile;
+
+        let mut video_config = Encoder::video_config();
+        video_config.change_codec_to_h264();
+        video_config.change_framerate_to_30();
+        let mut video_pkt_iter = Encoder::try_from(&video_config).unwrap().into_iter();
+        let image_sender = video_pkt_iter.image_sender();
+
+        // Four seconds of gray levels increasing with each frame, with a keyframe every two.
+        std::thread::spawn(move || {
+            for i in 0..120u8 {
+                let image = RgbImage::from_pixel(320, 240, image::Rgb([i * 2; 3]));
+                image_sender.send_rgb_image(&image).unwrap();
+            }
+            image_sender.finish().unwrap()
+        });
+
+        let named_temp_file = NamedTempFile::new().unwrap();
+        let url = format!("file://{}", named_temp_file.path().display());
+        let mut builder = Muxer::builder(Format::MpegTs).unwrap().open(&url).unwrap();
+        builder.add_stream(video_pkt_iter, StreamOptions::new()).unwrap();
+        builder.build().mux().unwrap();
+
+        // MPEG-TS timestamps do not begin at zero.
+        let mut demuxer = Demuxer::open(&url).unwrap();
+        let start = demuxer.start_time().unwrap();
+        assert!(start > 0);
+        let time_base = demuxer.stream(0).unwrap().time_base();
+
+        // Lands on the keyframe two seconds in rather than the first one.
+        demuxer.seek(Duration::from_secs(3)).unwrap();
+        let packet = demuxer.next().unwrap().unwrap();
+        let time = rescale(packet.pts, time_base, MICROSECONDS) - start;
+        assert!((1_900_000..=2_100_000).contains(&time), "{}", time);
+
+        // Frame 75 of 0.0333 seconds each, with a gray level of 150.
+        let image = thumbnail(&url, Duration::from_millis(2500)).unwrap();
+        let level = image.get_pixel(160, 120)[0];
+        assert!((140..=160).contains(&level), "{}", level);
+    }
+}
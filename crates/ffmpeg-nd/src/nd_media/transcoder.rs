@@ -0,0 +1,520 @@
+//! Re-encoding of a whole file: demux, decode, scale and resample, encode, mux.
+
+use super::decoder::Decoder;
+use super::demuxer::Demuxer;
+use super::encoder::{AudioConfig, Encoder, VideoConfig};
+use super::frame_data::copy_from_planes;
+use super::muxer::{Muxer, MuxerBuilder, StreamOptions};
+use crate::codec::Packet;
+use crate::format::{Format, Stream};
+use crate::swscale::{Interpolation, Scaler};
+use crate::util::{AV_NOPTS_VALUE, Frame, MediaType, PixelFormat, Rational, SampleFormat, rescale};
+use core::slice;
+use std::thread::JoinHandle;
+
+type ThreadResult = Result<(), String>;
+
+/// Converts the video, and optionally the audio, of `input` with the given encoder settings.
+/// Video is resampled to the configured frame rate by dropping or repeating frames.
+pub struct Transcoder {
+    input: String,
+    format: Option<Format>,
+    video: VideoConfig,
+    video_stream: Option<usize>,
+    audio: Option<AudioConfig>,
+    audio_stream: Option<usize>,
+}
+
+impl Transcoder {
+    pub fn new<U: AsRef<str>>(input: U, video: VideoConfig) -> Self {
+        Transcoder {
+            input: input.as_ref().to_string(),
+            format: None,
+            video,
+            video_stream: None,
+            audio: None,
+            audio_stream: None,
+        }
+    }
+
+    /// Output container. By default it follows the extension of the output URL.
+    pub fn format(mut self, value: Format) -> Self {
+        self.format = Some(value);
+        self
+    }
+
+    /// Also re-encode the audio. Without it the output has no audio.
+    pub fn audio(mut self, value: AudioConfig) -> Self {
+        self.audio = Some(value);
+        self
+    }
+
+    /// Index of the input stream to take the video from, instead of the default one.
+    pub fn video_stream(mut self, index: usize) -> Self {
+        self.video_stream = Some(index);
+        self
+    }
+
+    /// Index of the input stream to take the audio from, instead of the default one.
+    pub fn audio_stream(mut self, index: usize) -> Self {
+        self.audio_stream = Some(index);
+        self
+    }
+
+    /// Write the converted streams to `output`, e.g. `file:///tmp/out.mp4`.
+    pub fn run<U: AsRef<str>>(self, output: U) -> Result<(), Box<dyn std::error::Error>> {
+        let demuxer = Demuxer::open(&self.input)?;
+        let mut builder = match self.format {
+            Some(format) => Muxer::builder(format)?.open(output)?,
+            None => MuxerBuilder::from_url(output)?,
+        };
+        let mut threads: Vec<JoinHandle<ThreadResult>> = Vec::new();
+        let mut routes = Vec::new();
+
+        let video = select_stream(&demuxer, self.video_stream, MediaType::Video)?;
+        let decoder = Decoder::try_from(&video)?;
+        let pkt_iter = Encoder::try_from(&self.video)?.into_iter();
+        let start = video.start_time().unwrap_or(0);
+        let mut output = VideoOutput {
+            sender: pkt_iter.sender(),
+            size: (pkt_iter.width(), pkt_iter.height(), pkt_iter.pix_fmt()),
+            time_base: video.time_base(),
+            frame_time_base: Rational::new(pkt_iter.frame_rate().den, pkt_iter.frame_rate().num),
+            start,
+            scaler: None,
+            sent: 0,
+        };
+        let (sender, packets) = kanal::unbounded();
+        routes.push((usize::from(video.index()), sender));
+        threads.push(spawn(pkt_iter.sender(), move || {
+            decode(decoder, packets, |frame| output.push(frame))
+        }));
+        builder.add_stream(pkt_iter, StreamOptions::new())?;
+
+        if let Some(config) = &self.audio {
+            let audio = select_stream(&demuxer, self.audio_stream, MediaType::Audio)?;
+            let decoder = Decoder::try_from(&audio)?;
+            // Formats without a variant still resample, but a decoder without one cannot.
+            let sample_fmt = decoder.sample_fmt();
+            if sample_fmt == SampleFormat::None {
+                return Err(format!("Stream {} has no sample format", audio.index()).into());
+            }
+            let mut pkt_iter = Encoder::try_from(config)?.into_iter();
+            pkt_iter.set_input_audio(sample_fmt, decoder.sample_rate(), &decoder.ch_layout())?;
+            let mut output = AudioOutput {
+                sender: pkt_iter.sender(),
+                time_base: audio.time_base(),
+                sample_time_base: Rational::new(1, decoder.sample_rate()),
+                // Both outputs begin where the video does.
+                start: rescale(start, video.time_base(), audio.time_base()),
+                tolerance: i64::from(decoder.sample_rate() / 1000),
+                sent: 0,
+            };
+            let (sender, packets) = kanal::unbounded();
+            routes.push((usize::from(audio.index()), sender));
+            threads.push(spawn(pkt_iter.sender(), move || {
+                decode(decoder, packets, |frame| output.push(frame))
+            }));
+            builder.add_stream(pkt_iter, StreamOptions::new())?;
+        }
+
+        // Joined first, so that a read error is reported rather than its effect on decoding.
+        let router = PacketRouter { demuxer, routes };
+        threads.insert(0, std::thread::spawn(move || router.run()));
+
+        // Dropping the muxer closes the channels, so that a failed mux also ends the threads.
+        let result = builder.build().mux();
+        for thread in threads {
+            match thread.join() {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => return Err(e.into()),
+                Err(_) => return Err("Decoding thread panicked".into()),
+            }
+        }
+        result
+    }
+}
+
+/// Run `decode` on its own thread and end the stream with `None`. On failure the channel is
+/// closed, so that the muxer stops instead of waiting for more data.
+fn spawn<F>(sender: kanal::Sender<Option<Vec<u8>>>, decode: F) -> JoinHandle<ThreadResult>
+where
+    F: FnOnce() -> Result<(), Box<dyn std::error::Error>> + Send + 'static,
+{
+    std::thread::spawn(
+        move || match decode().and_then(|()| Ok(sender.send(None)?)) {
+            Ok(()) => Ok(()),
+            // The muxer stopped reading; it reports its own error.
+            Err(e) if e.is::<kanal::SendError>() => Ok(()),
+            Err(e) => {
+                let _ = sender.close();
+                Err(e.to_string())
+            }
+        },
+    )
+}
+
+fn select_stream(
+    demuxer: &Demuxer,
+    index: Option<usize>,
+    media_type: MediaType,
+) -> Result<Stream, Box<dyn std::error::Error>> {
+    let stream = match index {
+        Some(index) => demuxer.stream(index),
+        None => demuxer.best_stream(media_type),
+    };
+    match stream {
+        Some(stream) if stream.codec_type() == media_type => Ok(stream),
+        Some(stream) => Err(format!("Stream {} is not {:?}", stream.index(), media_type).into()),
+        None => Err(format!("No {:?} stream", media_type).into()),
+    }
+}
+
+/// Reads the input once and hands each packet to the thread decoding its stream. The channels
+/// are unbounded, so that a decoder waiting for one stream never blocks the others; at worst
+/// the compressed input is buffered.
+struct PacketRouter {
+    demuxer: Demuxer,
+    /// Input stream index and the channel of its decoding thread.
+    routes: Vec<(usize, kanal::Sender<Packet>)>,
+}
+
+// The demuxer reads a URL, not a Rust stream, so it holds nothing tied to the opening thread.
+unsafe impl Send for PacketRouter {}
+
+impl PacketRouter {
+    /// Packets of other streams are dropped. On failure the channels are closed instead of
+    /// ended, so that the decoders stop rather than flush a truncated stream.
+    fn run(self) -> ThreadResult {
+        for packet in self.demuxer {
+            let packet = match packet {
+                Ok(packet) => packet,
+                Err(e) => {
+                    for (_, sender) in &self.routes {
+                        let _ = sender.close();
+                    }
+                    return Err(e.to_string());
+                }
+            };
+            let index = usize::from(packet.stream_index());
+            if let Some((_, sender)) = self.routes.iter().find(|(i, _)| *i == index) {
+                // The decoding thread stopped; it or the muxer reports the error.
+                if sender.send(packet).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decode the packets of one stream to the end, flushing the decoder.
+fn decode<F>(
+    mut decoder: Decoder,
+    packets: kanal::Receiver<Packet>,
+    mut on_frame: F,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: FnMut(&Frame) -> Result<(), Box<dyn std::error::Error>>,
+{
+    loop {
+        let mut frame = Frame::new();
+        match decoder.receive_frame(&mut frame) {
+            Ok(()) => {
+                on_frame(&frame)?;
+                continue;
+            }
+            Err(e) if e.is_eof() => return Ok(()),
+            Err(e) if e.is_eagain() => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        match packets.recv() {
+            Ok(packet) => decoder.send_packet(&packet)?,
+            // The router ended the stream at the end of the input.
+            Err(kanal::ReceiveError::SendClosed) => decoder.send_packet_eof()?,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Turns decoded frames into constant frame rate data for the video encoder.
+struct VideoOutput {
+    sender: kanal::Sender<Option<Vec<u8>>>,
+    /// Width, height and pixel format the encoder takes.
+    size: (u16, u16, PixelFormat),
+    time_base: Rational,
+    /// Duration of one output frame.
+    frame_time_base: Rational,
+    start: i64,
+    /// The scaler, with the width, height and pixel format it takes.
+    scaler: Option<((u16, u16, PixelFormat), Scaler)>,
+    /// Number of frames sent so far.
+    sent: i64,
+}
+
+impl VideoOutput {
+    fn push(&mut self, frame: &Frame) -> Result<(), Box<dyn std::error::Error>> {
+        // Position of the frame in output frames; the first frame is always kept.
+        let position = match frame.best_effort_timestamp {
+            AV_NOPTS_VALUE => self.sent,
+            ts => rescale(ts - self.start, self.time_base, self.frame_time_base),
+        };
+        let repeat = match self.sent {
+            0 => position.max(0) + 1,
+            sent => position + 1 - sent,
+        };
+        if repeat <= 0 {
+            return Ok(());
+        }
+
+        let data = self.convert(frame)?;
+        for _ in 1..repeat {
+            self.sender.send(Some(data.clone()))?;
+        }
+        self.sender.send(Some(data))?;
+        self.sent += repeat;
+        Ok(())
+    }
+
+    fn convert(&mut self, frame: &Frame) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let src = (frame.width(), frame.height(), frame.pix_fmt());
+        if src == self.size {
+            return Ok(copy_from_planes(frame));
+        }
+        // Recreate the scaler if the input changes size or format mid-stream.
+        let scaler = match self.scaler.take() {
+            Some((size, scaler)) if size == src => scaler,
+            _ => Scaler::new(src, self.size, Interpolation::default())?,
+        };
+        let (_, scaler) = self.scaler.insert((src, scaler));
+        Ok(copy_from_planes(&scaler.scale(frame)?))
+    }
+}
+
+/// Turns decoded frames into continuous PCM for the audio encoder, which counts samples
+/// instead of reading timestamps. Gaps are filled with silence and overlaps are cut, so that
+/// the audio stays in sync with the video.
+struct AudioOutput {
+    sender: kanal::Sender<Option<Vec<u8>>>,
+    time_base: Rational,
+    /// Duration of one sample.
+    sample_time_base: Rational,
+    start: i64,
+    /// Drift in samples below which timestamps are taken as continuous, as they are rounded.
+    tolerance: i64,
+    /// Number of samples per channel sent so far.
+    sent: i64,
+}
+
+impl AudioOutput {
+    fn push(&mut self, frame: &Frame) -> Result<(), Box<dyn std::error::Error>> {
+        let drift = match frame.best_effort_timestamp {
+            AV_NOPTS_VALUE => 0,
+            ts => rescale(ts - self.start, self.time_base, self.sample_time_base) - self.sent,
+        };
+        let drift = match drift {
+            drift if drift.abs() <= self.tolerance => 0,
+            drift => drift,
+        };
+        if drift > 0 {
+            self.sender.send(Some(silence(frame, drift as usize)))?;
+        }
+        let nb_samples = frame.nb_samples() as i64;
+        let skip = (-drift).clamp(0, nb_samples);
+        if skip < nb_samples {
+            self.sender.send(Some(samples(frame, skip as usize)))?;
+        }
+        self.sent += drift.max(0) + nb_samples - skip;
+        Ok(())
+    }
+}
+
+/// The PCM of a decoded frame from sample `skip` on, laid out as `PacketIterator::sender`
+/// expects.
+fn samples(frame: &Frame, skip: usize) -> Vec<u8> {
+    let format = frame.sample_fmt();
+    let nb_channels = frame.ch_layout().nb_channels() as usize;
+    let (nb_planes, sample_size) = match format.is_planar() {
+        true => (nb_channels, format.bytes_per_sample()),
+        false => (1, format.bytes_per_sample() * nb_channels),
+    };
+    let plane_len = frame.nb_samples() * sample_size;
+    (0..nb_planes)
+        .flat_map(|i| {
+            let plane = unsafe { slice::from_raw_parts(*frame.extended_data.add(i), plane_len) };
+            &plane[skip * sample_size..]
+        })
+        .copied()
+        .collect()
+}
+
+/// `nb_samples` of silence in the sample format and channel layout of `frame`.
+fn silence(frame: &Frame, nb_samples: usize) -> Vec<u8> {
+    let format = frame.sample_fmt();
+    let nb_channels = frame.ch_layout().nb_channels() as usize;
+    // Unsigned samples are centered on 0x80.
+    let value = match format {
+        SampleFormat::U8 | SampleFormat::U8P => 0x80,
+        _ => 0,
+    };
+    vec![value; nb_samples * nb_channels * format.bytes_per_sample()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nd_media::test_util::{encode, encode_silence};
+
+    #[test]
+    fn test() {
+        use tempfile::NamedTempFile;
+
+        let video_pkt_iter = encode(&Encoder::video_config(), 30);
+        let audio_pkt_iter = encode_silence(50);
+
+        let input_file = NamedTempFile::new().unwrap();
+        let input = format!("file://{}", input_file.path().display());
+        let mut builder = Muxer::builder(Format::Mp4).unwrap().open(&input).unwrap();
+        builder
+            .add_stream(video_pkt_iter, StreamOptions::new())
+            .unwrap();
+        builder
+            .add_stream(audio_pkt_iter, StreamOptions::new())
+            .unwrap();
+        builder.build().mux().unwrap();
+
+        let mut video_config = Encoder::video_config();
+        video_config.change_codec_to_h264();
+        video_config.change_resolution_to_vga();
+        // The output format follows the extension.
+        let output_file = tempfile::Builder::new().suffix(".mkv").tempfile().unwrap();
+        let output = format!("file://{}", output_file.path().display());
+        Transcoder::new(&input, video_config)
+            .audio(Encoder::audio_config())
+            .run(&output)
+            .unwrap();
+
+        let demuxer = Demuxer::open(&output).unwrap();
+        let streams = demuxer.streams();
+        assert_eq!(streams.len(), 2);
+        assert_eq!(streams[0].codec_name(), "h264");
+        assert_eq!((streams[0].width(), streams[0].height()), (640, 480));
+        assert!(streams[1].codec_type() == MediaType::Audio);
+
+        let packets = demuxer.collect::<Result<Vec<Packet>, _>>().unwrap();
+        let video_packets = packets.iter().filter(|pkt| pkt.stream_index() == 0);
+        assert_eq!(video_packets.count(), 30);
+        assert!(packets.iter().any(|pkt| pkt.stream_index() == 1));
+    }
+
+    #[test]
+    fn should_resample_format_without_variant() {
+        use super::super::AudioContextBuilder;
+        use super::super::packet_iterator::PacketIterator;
+        use crate::codec::find_encoder_by_name;
+        use crate::util::ChannelLayout;
+        use ffi::AVSampleFormat_AV_SAMPLE_FMT_S64;
+        use tempfile::NamedTempFile;
+
+        let video_pkt_iter = encode(&Encoder::video_config(), 30);
+        // 64-bit PCM decodes to S64, which has no `SampleFormat` variant.
+        let s64 = SampleFormat::from(AVSampleFormat_AV_SAMPLE_FMT_S64);
+        let context = AudioContextBuilder::new(find_encoder_by_name("pcm_s64le").unwrap())
+            .sample_rate(48000)
+            .sample_fmt(s64)
+            .ch_layout(ChannelLayout::mono())
+            .build()
+            .unwrap();
+        let audio_pkt_iter = PacketIterator::from(context);
+        let audio_sample_sender = audio_pkt_iter.sender();
+
+        std::thread::spawn(move || {
+            for _ in 0..50 {
+                audio_sample_sender.send(Some(vec![0u8; 960 * 8])).unwrap();
+            }
+            audio_sample_sender.send(None).unwrap()
+        });
+
+        let input_file = NamedTempFile::new().unwrap();
+        let input = format!("file://{}", input_file.path().display());
+        let mut builder = Muxer::builder(Format::Matroska)
+            .unwrap()
+            .open(&input)
+            .unwrap();
+        builder
+            .add_stream(video_pkt_iter, StreamOptions::new())
+            .unwrap();
+        builder
+            .add_stream(audio_pkt_iter, StreamOptions::new())
+            .unwrap();
+        builder.build().mux().unwrap();
+        let decoder = Decoder::try_from(&Demuxer::open(&input).unwrap().streams()[1]).unwrap();
+        assert_eq!(decoder.sample_fmt(), s64);
+
+        let output_file = tempfile::Builder::new().suffix(".mkv").tempfile().unwrap();
+        let output = format!("file://{}", output_file.path().display());
+        Transcoder::new(&input, Encoder::video_config())
+            .audio(Encoder::audio_config())
+            .run(&output)
+            .unwrap();
+
+        let demuxer = Demuxer::open(&output).unwrap();
+        assert_eq!(demuxer.streams()[1].codec_name(), "aac");
+        let packets = demuxer.collect::<Result<Vec<Packet>, _>>().unwrap();
+        assert!(packets.iter().any(|pkt| pkt.stream_index() == 1));
+    }
+
+    #[test]
+    fn test_audio_output() {
+        use crate::util::ChannelLayout;
+
+        // Stereo S16P frames of 10 samples, each sample holding its index in both bytes.
+        let frame = |pts: i64| {
+            let mut frame = Frame::new();
+            frame.set_sample_format(SampleFormat::S16P);
+            frame.set_ch_layout(&ChannelLayout::stereo()).unwrap();
+            frame.set_nb_samples(10);
+            frame.get_buffer().unwrap();
+            for i in 0..2 {
+                let plane = unsafe { slice::from_raw_parts_mut(*frame.extended_data.add(i), 20) };
+                for (j, byte) in plane.iter_mut().enumerate() {
+                    *byte = (j / 2) as u8;
+                }
+            }
+            frame.best_effort_timestamp = pts;
+            frame
+        };
+        let (sender, receiver) = kanal::unbounded();
+        let mut output = AudioOutput {
+            sender,
+            time_base: Rational::new(1, 1000),
+            sample_time_base: Rational::new(1, 1000),
+            start: 0,
+            tolerance: 1,
+            sent: 0,
+        };
+        for pts in [0, 15, 20, 30, 41] {
+            output.push(&frame(pts)).unwrap();
+        }
+        assert_eq!(output.sent, 50);
+
+        let plane = (0..10).flat_map(|i| [i, i]).collect::<Vec<u8>>();
+        let whole = [plane.clone(), plane.clone()].concat();
+        let expected = [
+            whole.clone(),
+            // A gap of 5 samples.
+            vec![0; 20],
+            whole.clone(),
+            // Overlaps the previous frame by 5 samples.
+            [&plane[10..], &plane[10..]].concat(),
+            whole.clone(),
+            // Off by one sample, within the tolerance.
+            whole,
+        ];
+        for data in expected {
+            assert_eq!(receiver.recv().unwrap(), Some(data));
+        }
+        assert!(receiver.is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 //! This module is not contained within the FFmpeg API but provides a useful builder for creating codec contexts.
 
 use crate::codec::{Codec, Context};
-use crate::util::{Error, PixelFormat, Rational};
+use crate::util::{ColorRange, ColorSpace, Dictionary, Error, PixelFormat, Rational};
 
 pub struct VideoContextBuilder {
     codec: Codec,
@@ -13,6 +13,9 @@ pub struct VideoContextBuilder {
     gop_size: Option<u8>,
     max_b_frames: Option<u8>,
     pix_fmt: Option<PixelFormat>,
+    colorspace: Option<ColorSpace>,
+    color_range: Option<ColorRange>,
+    options: Dictionary,
 }
 
 impl VideoContextBuilder {
@@ -27,6 +30,9 @@ impl VideoContextBuilder {
             gop_size: None,
             max_b_frames: None,
             pix_fmt: None,
+            colorspace: None,
+            color_range: None,
+            options: Dictionary::new(),
         }
     }
 
@@ -70,6 +76,22 @@ impl VideoContextBuilder {
         self
     }
 
+    pub fn colorspace<C: Into<ColorSpace>>(mut self, value: C) -> Self {
+        self.colorspace = Some(value.into());
+        self
+    }
+
+    pub fn color_range<C: Into<ColorRange>>(mut self, value: C) -> Self {
+        self.color_range = Some(value.into());
+        self
+    }
+
+    /// Codec and private codec options, e.g. `preset` for libx264. Unknown keys fail `build`.
+    pub fn options(mut self, value: Dictionary) -> Self {
+        self.options = value;
+        self
+    }
+
     pub fn build(self) -> Result<Context, Error> {
         let mut ctx = Context::from(self.codec);
         if let Some(width) = self.width {
@@ -96,8 +118,14 @@ impl VideoContextBuilder {
         if let Some(pix_fmt) = self.pix_fmt {
             ctx.set_pix_fmt(pix_fmt);
         }
+        if let Some(colorspace) = self.colorspace {
+            ctx.set_colorspace(colorspace);
+        }
+        if let Some(color_range) = self.color_range {
+            ctx.set_color_range(color_range);
+        }
 
-        ctx.open()?;
+        ctx.open_with_options(self.options)?.ensure_consumed()?;
         Ok(ctx)
     }
 }
@@ -123,5 +151,16 @@ mod tests {
             .unwrap();
 
         let _context = Name::default().default_video_context();
+
+        let options = Dictionary::try_from([("g", "30"), ("not_an_option", "1")]).unwrap();
+        let err = VideoContextBuilder::new(find_encoder_by_name(&Name::default()).unwrap())
+            .pix_fmt(PixelFormat::default())
+            .width(320)
+            .height(240)
+            .time_base([1, 30])
+            .options(options)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, Error::UnusedOptions(keys) if keys == ["not_an_option"]));
     }
 }
@@ -0,0 +1,3 @@
+mod resampler;
+
+pub use resampler::Resampler;
@@ -0,0 +1,193 @@
+use crate::util::{ChannelLayout, Error, Frame, SampleFormat};
+use core::slice;
+use ffi::*;
+
+/// Converts audio frames between sample formats, sample rates and channel layouts.
+pub struct Resampler {
+    ptr: *mut SwrContext,
+    src: (SampleFormat, u32, ChannelLayout),
+    dst: (SampleFormat, u32, ChannelLayout),
+}
+
+impl Resampler {
+    pub fn as_ptr(&self) -> *const SwrContext {
+        self.ptr as *const _
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut SwrContext {
+        self.ptr
+    }
+}
+
+impl Resampler {
+    pub fn new(
+        (src_format, src_rate, src_layout): (SampleFormat, u32, &ChannelLayout),
+        (dst_format, dst_rate, dst_layout): (SampleFormat, u32, &ChannelLayout),
+    ) -> Result<Self, Error> {
+        let mut ptr = core::ptr::null_mut();
+        match unsafe {
+            swr_alloc_set_opts2(
+                &mut ptr,
+                dst_layout.as_ptr(),
+                dst_format.into(),
+                dst_rate.try_into().unwrap(),
+                src_layout.as_ptr(),
+                src_format.into(),
+                src_rate.try_into().unwrap(),
+                0,
+                core::ptr::null_mut(),
+            )
+        } {
+            e if e < 0 => return Err(e.into()),
+            _ => (),
+        }
+        match unsafe { swr_init(ptr) } {
+            e if e < 0 => {
+                unsafe { swr_free(&mut ptr) };
+                Err(e.into())
+            }
+            _ => Ok(Resampler {
+                ptr,
+                src: (src_format, src_rate, src_layout.clone()),
+                dst: (dst_format, dst_rate, dst_layout.clone()),
+            }),
+        }
+    }
+
+    /// Convert `src` into a newly allocated frame. Some of the samples may be held back
+    /// until the next call or `flush`.
+    pub fn convert(&mut self, src: &Frame) -> Result<Frame, Error> {
+        let mut dst = self.output_frame()?;
+        match unsafe { swr_convert_frame(self.ptr, dst.as_mut_ptr(), src.as_ptr()) } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(dst),
+        }
+    }
+
+    /// Drain the samples held back at the end of the input. Returns `None` once empty.
+    pub fn flush(&mut self) -> Result<Option<Frame>, Error> {
+        let mut dst = self.output_frame()?;
+        match unsafe { swr_convert_frame(self.ptr, dst.as_mut_ptr(), core::ptr::null()) } {
+            e if e < 0 => Err(e.into()),
+            _ if dst.nb_samples() == 0 => Ok(None),
+            _ => Ok(Some(dst)),
+        }
+    }
+
+    /// Like `convert`, on PCM bytes laid out as `PacketIterator::sender` expects.
+    pub fn convert_samples(&mut self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let (format, rate, layout) = &self.src;
+        let nb_channels = layout.nb_channels() as usize;
+        let sample_size = format.bytes_per_sample() * nb_channels;
+        if data.len() % sample_size != 0 {
+            return Err(format!(
+                "PCM data of {} bytes is not a whole number of {}-byte samples",
+                data.len(),
+                sample_size
+            )
+            .into());
+        }
+
+        let mut src = Frame::new();
+        src.set_sample_format(*format);
+        src.set_sample_rate(*rate);
+        src.set_ch_layout(layout)?;
+        src.set_nb_samples(data.len() / sample_size);
+        src.get_buffer()?;
+        if format.is_planar() {
+            let plane_len = data.len() / nb_channels;
+            for (i, plane) in data.chunks(plane_len).enumerate() {
+                let dst = unsafe { slice::from_raw_parts_mut(*src.extended_data.add(i), plane_len) };
+                dst.copy_from_slice(plane);
+            }
+        } else {
+            let dst = unsafe { slice::from_raw_parts_mut(src.data[0], data.len()) };
+            dst.copy_from_slice(data);
+        }
+
+        Ok(samples(&self.convert(&src)?))
+    }
+
+    /// Like `flush`, returning PCM bytes.
+    pub fn flush_samples(&mut self) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::new();
+        while let Some(frame) = self.flush()? {
+            data.extend(samples(&frame));
+        }
+        Ok(data)
+    }
+
+    fn output_frame(&self) -> Result<Frame, Error> {
+        let (format, rate, layout) = &self.dst;
+        let mut frame = Frame::new();
+        frame.set_sample_format(*format);
+        frame.set_sample_rate(*rate);
+        frame.set_ch_layout(layout)?;
+        Ok(frame)
+    }
+}
+
+/// The PCM of a frame, with planes back to back if planar.
+fn samples(frame: &Frame) -> Vec<u8> {
+    let format = frame.sample_fmt();
+    let nb_channels = frame.ch_layout().nb_channels() as usize;
+    if format.is_planar() {
+        let plane_len = frame.nb_samples() * format.bytes_per_sample();
+        (0..nb_channels)
+            .flat_map(|i| unsafe { slice::from_raw_parts(*frame.extended_data.add(i), plane_len) })
+            .copied()
+            .collect()
+    } else {
+        let len = frame.nb_samples() * format.bytes_per_sample() * nb_channels;
+        unsafe { slice::from_raw_parts(frame.data[0], len) }.to_vec()
+    }
+}
+
+impl Drop for Resampler {
+    fn drop(&mut self) {
+        unsafe { swr_free(&mut self.ptr) }
+    }
+}
+
+unsafe impl Send for Resampler {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut resampler = Resampler::new(
+            (SampleFormat::S16, 44100, &ChannelLayout::stereo()),
+            (SampleFormat::FLTP, 48000, &ChannelLayout::stereo()),
+        )
+        .unwrap();
+
+        // One second of silence in 441-sample chunks.
+        let mut data = Vec::new();
+        for _ in 0..100 {
+            data.extend(resampler.convert_samples(&[0u8; 441 * 4]).unwrap());
+        }
+        data.extend(resampler.flush_samples().unwrap());
+
+        let nb_samples = data.len() / (4 * 2);
+        assert!((47990..=48010).contains(&nb_samples), "{}", nb_samples);
+        assert!(data.iter().all(|&byte| byte == 0));
+
+        resampler.convert_samples(&[0u8; 3]).unwrap_err();
+    }
+
+    #[test]
+    fn should_convert_format_without_variant() {
+        let s64 = SampleFormat::from(AVSampleFormat_AV_SAMPLE_FMT_S64);
+        let mut resampler = Resampler::new(
+            (s64, 48000, &ChannelLayout::mono()),
+            (SampleFormat::S16, 48000, &ChannelLayout::mono()),
+        )
+        .unwrap();
+
+        let mut data = resampler.convert_samples(&[0u8; 480 * 8]).unwrap();
+        data.extend(resampler.flush_samples().unwrap());
+        assert_eq!(data.len(), 480 * 2);
+    }
+}
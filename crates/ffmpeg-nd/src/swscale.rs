@@ -0,0 +1,5 @@
+mod interpolation;
+mod scaler;
+
+pub use interpolation::Interpolation;
+pub use scaler::Scaler;
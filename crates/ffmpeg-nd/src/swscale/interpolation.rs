@@ -0,0 +1,28 @@
+use ffi::*;
+use std::os::raw::c_int;
+
+/// Scaling algorithm of a `Scaler`.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum Interpolation {
+    FastBilinear,
+    #[default]
+    Bilinear,
+    Bicubic,
+    /// Nearest neighbor.
+    Point,
+    Area,
+    Lanczos,
+}
+
+impl Into<c_int> for Interpolation {
+    fn into(self) -> c_int {
+        match self {
+            Interpolation::FastBilinear => SWS_FAST_BILINEAR,
+            Interpolation::Bilinear => SWS_BILINEAR,
+            Interpolation::Bicubic => SWS_BICUBIC,
+            Interpolation::Point => SWS_POINT,
+            Interpolation::Area => SWS_AREA,
+            Interpolation::Lanczos => SWS_LANCZOS,
+        }
+    }
+}
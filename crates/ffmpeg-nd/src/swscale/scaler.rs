@@ -0,0 +1,130 @@
+use super::interpolation::Interpolation;
+use crate::util::{Error, Frame, PixelFormat};
+use ffi::*;
+
+/// Converts video frames of one size and pixel format into another.
+pub struct Scaler {
+    ptr: *mut SwsContext,
+    src: (u16, u16, PixelFormat),
+    dst: (u16, u16, PixelFormat),
+}
+
+impl Scaler {
+    pub fn as_ptr(&self) -> *const SwsContext {
+        self.ptr as *const _
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut SwsContext {
+        self.ptr
+    }
+}
+
+impl Scaler {
+    pub fn new(
+        (src_width, src_height, src_format): (u16, u16, PixelFormat),
+        (dst_width, dst_height, dst_format): (u16, u16, PixelFormat),
+        interpolation: Interpolation,
+    ) -> Result<Self, Error> {
+        let ptr = unsafe {
+            sws_getContext(
+                src_width.into(),
+                src_height.into(),
+                src_format.into(),
+                dst_width.into(),
+                dst_height.into(),
+                dst_format.into(),
+                interpolation.into(),
+                core::ptr::null_mut(),
+                core::ptr::null_mut(),
+                core::ptr::null(),
+            )
+        };
+        if ptr.is_null() {
+            return Err(format!(
+                "Cannot scale {}x{} {:?} to {}x{} {:?}",
+                src_width, src_height, src_format, dst_width, dst_height, dst_format
+            )
+            .into());
+        }
+        Ok(Scaler {
+            ptr,
+            src: (src_width, src_height, src_format),
+            dst: (dst_width, dst_height, dst_format),
+        })
+    }
+
+    /// Scale into a newly allocated frame.
+    pub fn scale(&mut self, src: &Frame) -> Result<Frame, Error> {
+        let mut dst = Frame::new();
+        let (width, height, format) = self.dst;
+        dst.set_width(width);
+        dst.set_height(height);
+        dst.set_format(format);
+        dst.get_buffer()?;
+        self.scale_into(src, &mut dst)?;
+        Ok(dst)
+    }
+
+    /// Scale into `dst`, which must be writable and match the output size and format.
+    pub fn scale_into(&mut self, src: &Frame, dst: &mut Frame) -> Result<(), Error> {
+        if (src.width(), src.height(), src.pix_fmt()) != self.src {
+            return Err("Source frame does not match the scaler input".into());
+        }
+        if (dst.width(), dst.height(), dst.pix_fmt()) != self.dst {
+            return Err("Destination frame does not match the scaler output".into());
+        }
+        match unsafe { sws_scale_frame(self.ptr, dst.as_mut_ptr(), src.as_ptr()) } {
+            e if e < 0 => Err(e.into()),
+            _ => {
+                dst.pts = src.pts;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Drop for Scaler {
+    fn drop(&mut self) {
+        unsafe { sws_freeContext(self.ptr) }
+    }
+}
+
+unsafe impl Send for Scaler {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut src = Frame::new();
+        src.set_width(64);
+        src.set_height(48);
+        src.set_format(PixelFormat::YUV420P);
+        src.get_buffer().unwrap();
+        src.set_pts(7);
+        unsafe {
+            core::ptr::write_bytes(src.data[0], 235, src.linesize[0] as usize * 48);
+            core::ptr::write_bytes(src.data[1], 128, src.linesize[1] as usize * 24);
+            core::ptr::write_bytes(src.data[2], 128, src.linesize[2] as usize * 24);
+        }
+
+        let mut scaler = Scaler::new(
+            (64, 48, PixelFormat::YUV420P),
+            (32, 24, PixelFormat::RGB24),
+            Interpolation::Bicubic,
+        )
+        .unwrap();
+        let dst = scaler.scale(&src).unwrap();
+        assert_eq!((dst.width(), dst.height(), dst.pix_fmt()), (32, 24, PixelFormat::RGB24));
+        assert_eq!(dst.pts(), 7);
+        assert!(unsafe { *dst.data[0] } >= 250);
+
+        let mut wrong = Frame::new();
+        wrong.set_width(16);
+        wrong.set_height(16);
+        wrong.set_format(PixelFormat::RGB24);
+        wrong.get_buffer().unwrap();
+        scaler.scale_into(&src, &mut wrong).unwrap_err();
+    }
+}
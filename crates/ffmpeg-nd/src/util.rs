@@ -1,4 +1,7 @@
 mod _util;
+mod channel_layout;
+mod color;
+mod dictionary;
 mod error;
 mod frame;
 mod log;
@@ -7,15 +10,21 @@ mod media;
 mod opt;
 mod pix_fmt;
 mod rational;
+mod sample_fmt;
 mod utils;
 
+pub(crate) use _util::{AV_NOPTS_VALUE, AVERROR_EIO, AVERROR_EOF};
+pub use channel_layout::*;
+pub(crate) use color::*;
+pub use dictionary::Dictionary;
 pub(crate) use error::*;
-pub(crate) use frame::*;
+pub use frame::*;
 pub(crate) use mathematics::*;
 pub(crate) use media::*;
 pub(crate) use opt::*;
-pub(crate) use pix_fmt::*;
+pub use pix_fmt::*;
 pub(crate) use rational::*;
+pub use sample_fmt::*;
 
 pub use log::Level;
 pub use utils::version_info;
@@ -1,9 +1,14 @@
 // https://github.com/FFmpeg/FFmpeg/blob/37507c6a78ab63bbf8dda1c0525545d30877bca2/libavutil/error.h#L57
 pub const AVERROR_EOF: i32 = macros::fferrtag(b'E', b'O', b'F', b' ');
 
+// https://github.com/FFmpeg/FFmpeg/blob/37507c6a78ab63bbf8dda1c0525545d30877bca2/libavutil/error.h#L58
+pub const AVERROR_EXIT: i32 = macros::fferrtag(b'E', b'X', b'I', b'T');
+
 // https://github.com/FFmpeg/FFmpeg/blob/37507c6a78ab63bbf8dda1c0525545d30877bca2/doc/examples/encode_video.c#L56
 pub const AVERROR_EAGAIN: i32 = macros::averror(errno::EAGAIN);
 
+pub const AVERROR_EIO: i32 = macros::averror(errno::EIO);
+
 mod macros {
     // https://github.com/FFmpeg/FFmpeg/blob/37507c6a78ab63bbf8dda1c0525545d30877bca2/libavutil/error.h#L49
     pub const fn fferrtag(a: u8, b: u8, c: u8, d: u8) -> i32 {
@@ -20,4 +25,8 @@ mod macros {
 // https://github.com/torvalds/linux/blob/dfd4b508c8c6106083698a0dd5e35aecc7c48725/include/uapi/asm-generic/errno-base.h#L15
 mod errno {
     pub const EAGAIN: i32 = ffi::EAGAIN;
+    pub const EIO: i32 = ffi::EIO;
 }
+
+// https://github.com/FFmpeg/FFmpeg/blob/37507c6a78ab63bbf8dda1c0525545d30877bca2/libavutil/avutil.h#L248
+pub const AV_NOPTS_VALUE: i64 = i64::MIN;
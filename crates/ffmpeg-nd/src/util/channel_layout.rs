@@ -0,0 +1,79 @@
+use crate::util::Error;
+use ffi::*;
+
+/// Owned `AVChannelLayout`.
+pub struct ChannelLayout(AVChannelLayout);
+
+impl ChannelLayout {
+    pub fn as_ptr(&self) -> *const AVChannelLayout {
+        &self.0
+    }
+}
+
+impl ChannelLayout {
+    /// The default native layout for the given number of channels.
+    pub fn default_for(nb_channels: u16) -> Self {
+        let mut layout: AVChannelLayout = unsafe { core::mem::zeroed() };
+        unsafe { av_channel_layout_default(&mut layout, nb_channels.into()) };
+        ChannelLayout(layout)
+    }
+
+    pub fn mono() -> Self {
+        Self::default_for(1)
+    }
+
+    pub fn stereo() -> Self {
+        Self::default_for(2)
+    }
+
+    pub fn nb_channels(&self) -> u16 {
+        self.0.nb_channels.try_into().unwrap()
+    }
+
+    /// Copy this layout into an `AVChannelLayout` owned by FFmpeg, e.g. of a codec context or frame.
+    pub fn copy_to(&self, dst: *mut AVChannelLayout) -> Result<(), Error> {
+        match unsafe { av_channel_layout_copy(dst, self.as_ptr()) } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<*const AVChannelLayout> for ChannelLayout {
+    fn from(ptr: *const AVChannelLayout) -> Self {
+        let mut layout: AVChannelLayout = unsafe { core::mem::zeroed() };
+        unsafe { av_channel_layout_copy(&mut layout, ptr) };
+        ChannelLayout(layout)
+    }
+}
+
+impl Clone for ChannelLayout {
+    fn clone(&self) -> Self {
+        self.as_ptr().into()
+    }
+}
+
+impl PartialEq for ChannelLayout {
+    fn eq(&self, other: &Self) -> bool {
+        unsafe { av_channel_layout_compare(self.as_ptr(), other.as_ptr()) == 0 }
+    }
+}
+
+impl Drop for ChannelLayout {
+    fn drop(&mut self) {
+        unsafe { av_channel_layout_uninit(&mut self.0) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let stereo = ChannelLayout::stereo();
+        assert_eq!(stereo.nb_channels(), 2);
+        assert!(stereo == stereo.clone());
+        assert!(stereo != ChannelLayout::mono());
+    }
+}
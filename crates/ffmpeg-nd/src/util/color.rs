@@ -0,0 +1,59 @@
+#![allow(non_upper_case_globals)]
+use ffi::*;
+
+/// YUV matrix coefficients of a frame or codec context.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ColorSpace {
+    Unspecified,
+    BT601,
+    BT709,
+}
+
+impl From<AVColorSpace> for ColorSpace {
+    fn from(value: AVColorSpace) -> Self {
+        match value {
+            AVColorSpace_AVCOL_SPC_BT470BG | AVColorSpace_AVCOL_SPC_SMPTE170M => ColorSpace::BT601,
+            AVColorSpace_AVCOL_SPC_BT709 => ColorSpace::BT709,
+            _ => ColorSpace::Unspecified,
+        }
+    }
+}
+
+impl Into<AVColorSpace> for ColorSpace {
+    fn into(self) -> AVColorSpace {
+        match self {
+            ColorSpace::Unspecified => AVColorSpace_AVCOL_SPC_UNSPECIFIED,
+            ColorSpace::BT601 => AVColorSpace_AVCOL_SPC_SMPTE170M,
+            ColorSpace::BT709 => AVColorSpace_AVCOL_SPC_BT709,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ColorRange {
+    Unspecified,
+    /// Limited range, e.g. 16-235 for 8-bit luma.
+    MPEG,
+    /// Full range, 0-255 for 8-bit.
+    JPEG,
+}
+
+impl From<AVColorRange> for ColorRange {
+    fn from(value: AVColorRange) -> Self {
+        match value {
+            AVColorRange_AVCOL_RANGE_MPEG => ColorRange::MPEG,
+            AVColorRange_AVCOL_RANGE_JPEG => ColorRange::JPEG,
+            _ => ColorRange::Unspecified,
+        }
+    }
+}
+
+impl Into<AVColorRange> for ColorRange {
+    fn into(self) -> AVColorRange {
+        match self {
+            ColorRange::Unspecified => AVColorRange_AVCOL_RANGE_UNSPECIFIED,
+            ColorRange::MPEG => AVColorRange_AVCOL_RANGE_MPEG,
+            ColorRange::JPEG => AVColorRange_AVCOL_RANGE_JPEG,
+        }
+    }
+}
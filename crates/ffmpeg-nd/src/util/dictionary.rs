@@ -0,0 +1,210 @@
+use crate::util::Error;
+use core::ffi::CStr;
+use ffi::*;
+use std::collections::HashMap;
+use std::ffi::CString;
+
+/// Owned `AVDictionary` of string options for protocols, muxers and codecs.
+///
+/// Functions taking options hand back the entries FFmpeg did not recognize.
+pub struct Dictionary {
+    ptr: *mut AVDictionary,
+}
+
+impl Dictionary {
+    pub fn as_ptr(&self) -> *const AVDictionary {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut AVDictionary {
+        self.ptr
+    }
+
+    /// For FFmpeg functions taking `AVDictionary **`, which replace the dictionary in place.
+    pub fn as_mut_ptr_ptr(&mut self) -> *mut *mut AVDictionary {
+        &mut self.ptr
+    }
+}
+
+impl Dictionary {
+    pub fn new() -> Self {
+        Dictionary {
+            ptr: core::ptr::null_mut(),
+        }
+    }
+
+    /// Insert or replace an entry. Fails if `key` or `value` contains a NUL byte.
+    pub fn set<K: AsRef<str>, V: AsRef<str>>(&mut self, key: K, value: V) -> Result<(), Error> {
+        let (Ok(c_key), Ok(c_value)) = (CString::new(key.as_ref()), CString::new(value.as_ref()))
+        else {
+            let key = key.as_ref().escape_default();
+            return Err(format!("Option '{}' contains a NUL byte", key).into());
+        };
+        match unsafe { av_dict_set(&mut self.ptr, c_key.as_ptr(), c_value.as_ptr(), 0) } {
+            e if e < 0 => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// `None` also for a key with a NUL byte, which no entry can have.
+    pub fn get<K: AsRef<str>>(&self, key: K) -> Option<String> {
+        let Ok(key) = CString::new(key.as_ref()) else {
+            return None;
+        };
+        let entry = unsafe { av_dict_get(self.ptr, key.as_ptr(), core::ptr::null(), 0) };
+        if entry.is_null() {
+            None
+        } else {
+            Some(
+                unsafe { CStr::from_ptr((*entry).value) }
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { av_dict_count(self.ptr) }.try_into().unwrap()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Entries in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (String, String)> + '_ {
+        let mut entry = core::ptr::null();
+        core::iter::from_fn(move || {
+            entry = unsafe { av_dict_iterate(self.ptr, entry) };
+            if entry.is_null() {
+                return None;
+            }
+            let (key, value) =
+                unsafe { (CStr::from_ptr((*entry).key), CStr::from_ptr((*entry).value)) };
+            Some((
+                key.to_string_lossy().into_owned(),
+                value.to_string_lossy().into_owned(),
+            ))
+        })
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.iter().map(|(key, _)| key).collect()
+    }
+
+    /// Fails on the first key or value with a NUL byte, see `set`.
+    fn from_entries<K, V, I>(entries: I) -> Result<Self, Error>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut dictionary = Self::new();
+        for (key, value) in entries {
+            dictionary.set(key, value)?;
+        }
+        Ok(dictionary)
+    }
+
+    /// `Error::UnusedOptions` unless empty, for the entries left over after FFmpeg consumed the rest.
+    pub fn ensure_consumed(&self) -> Result<(), Error> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err(Error::UnusedOptions(self.keys())),
+        }
+    }
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for Dictionary {
+    fn clone(&self) -> Self {
+        self.as_ptr().into()
+    }
+}
+
+impl Drop for Dictionary {
+    fn drop(&mut self) {
+        unsafe { av_dict_free(&mut self.ptr) }
+    }
+}
+
+unsafe impl Send for Dictionary {}
+
+/// Copies a dictionary owned by FFmpeg, e.g. the metadata of a stream.
+impl From<*const AVDictionary> for Dictionary {
+    fn from(ptr: *const AVDictionary) -> Self {
+        let mut dictionary = Self::new();
+        unsafe { av_dict_copy(&mut dictionary.ptr, ptr, 0) };
+        dictionary
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>, const N: usize> TryFrom<[(K, V); N]> for Dictionary {
+    type Error = Error;
+
+    fn try_from(entries: [(K, V); N]) -> Result<Self, Self::Error> {
+        Self::from_entries(entries)
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> TryFrom<Vec<(K, V)>> for Dictionary {
+    type Error = Error;
+
+    fn try_from(entries: Vec<(K, V)>) -> Result<Self, Self::Error> {
+        Self::from_entries(entries)
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> TryFrom<HashMap<K, V>> for Dictionary {
+    type Error = Error;
+
+    fn try_from(map: HashMap<K, V>) -> Result<Self, Self::Error> {
+        Self::from_entries(map)
+    }
+}
+
+impl From<&Dictionary> for HashMap<String, String> {
+    fn from(dictionary: &Dictionary) -> Self {
+        dictionary.iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut dictionary = Dictionary::new();
+        assert!(dictionary.is_empty());
+        dictionary.set("preset", "fast").unwrap();
+        dictionary.set("crf", "23").unwrap();
+        dictionary.set("preset", "slow").unwrap();
+        assert_eq!(dictionary.len(), 2);
+        assert_eq!(dictionary.get("preset").as_deref(), Some("slow"));
+        assert_eq!(dictionary.get("tune"), None);
+        assert_eq!(dictionary.keys(), ["crf", "preset"]);
+
+        let map = HashMap::from([("movflags", "faststart")]);
+        let copy = Dictionary::try_from(map).unwrap().clone();
+        assert_eq!(HashMap::from(&copy)["movflags"], "faststart");
+
+        // Would otherwise be cut at the NUL byte.
+        assert!(dictionary.set("preset\0x", "fast").is_err());
+        assert!(dictionary.set("preset", "fa\0st").is_err());
+        assert!(Dictionary::try_from([("crf", "2\03")]).is_err());
+        assert_eq!(dictionary.get("preset\0x"), None);
+        assert_eq!(dictionary.get("preset").as_deref(), Some("slow"));
+
+        match dictionary.ensure_consumed() {
+            Err(Error::UnusedOptions(keys)) => assert_eq!(keys.len(), 2),
+            _ => panic!(),
+        }
+        Dictionary::new().ensure_consumed().unwrap();
+    }
+}
@@ -7,6 +7,20 @@ pub enum Error {
     /// For AVERROR(e) wrapping POSIX error codes, e.g. AVERROR(EAGAIN).
     Other { errno: c_int, backtrace: Backtrace },
 
+    /// FFmpeg gave up because an `Interrupt` was cancelled or its deadline passed (`AVERROR_EXIT`).
+    Interrupted,
+
+    /// Options FFmpeg did not recognize, e.g. misspelled keys of a `Dictionary`.
+    UnusedOptions(Vec<String>),
+
+    /// The container format cannot store the codec of a stream. `supported` lists the codecs of
+    /// the same media type it can store.
+    UnsupportedCodec {
+        codec: String,
+        format: String,
+        supported: Vec<String>,
+    },
+
     /// For non-FFmpeg string errors.
     Message(String),
 }
@@ -16,6 +30,10 @@ impl Error {
         matches!(self, Error::Other { errno: e, backtrace: _ } if *e == crate::util::_util::AVERROR_EAGAIN)
     }
 
+    pub fn is_interrupted(&self) -> bool {
+        matches!(self, Error::Interrupted)
+    }
+
     pub fn is_eof(&self) -> bool {
         matches!(self, Error::Other { errno: e, backtrace: _ } if *e == crate::util::_util::AVERROR_EOF)
     }
@@ -29,6 +47,19 @@ impl fmt::Display for Error {
                 "FFmpeg error with code: {}\nBacktrace: {:?}",
                 errno, backtrace
             ),
+            Error::Interrupted => write!(f, "Interrupted"),
+            Error::UnusedOptions(keys) => write!(f, "Unused options: {}", keys.join(", ")),
+            Error::UnsupportedCodec {
+                codec,
+                format,
+                supported,
+            } => write!(
+                f,
+                "Codec {} is not supported by {}, use one of: {}",
+                codec,
+                format,
+                supported.join(", ")
+            ),
             Error::Message(msg) => write!(f, "Error: {}", msg),
         }
     }
@@ -39,6 +70,7 @@ impl std::error::Error for Error {}
 impl From<c_int> for Error {
     fn from(value: c_int) -> Error {
         match value {
+            crate::util::_util::AVERROR_EXIT => Error::Interrupted,
             e => Error::Other {
                 errno: e,
                 backtrace: Backtrace::capture(),
@@ -1,5 +1,7 @@
 use crate::codec::Context;
-use crate::util::{Error, PixelFormat};
+use crate::util::{
+    ChannelLayout, ColorRange, ColorSpace, Error, MediaType, PixelFormat, SampleFormat,
+};
 use core::ops::{Deref, DerefMut};
 use core::ptr::null_mut;
 use ffi::*;
@@ -48,6 +50,38 @@ impl Frame {
 }
 
 impl Frame {
+    pub fn width(&self) -> u16 {
+        self.width.try_into().unwrap()
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height.try_into().unwrap()
+    }
+
+    pub fn pix_fmt(&self) -> PixelFormat {
+        self.format.into()
+    }
+
+    pub fn colorspace(&self) -> ColorSpace {
+        self.colorspace.into()
+    }
+
+    pub fn set_colorspace(&mut self, value: ColorSpace) {
+        self.colorspace = value.into();
+    }
+
+    pub fn color_range(&self) -> ColorRange {
+        self.color_range.into()
+    }
+
+    pub fn set_color_range(&mut self, value: ColorRange) {
+        self.color_range = value.into();
+    }
+
+    pub fn pts(&self) -> i64 {
+        self.pts
+    }
+
     pub fn set_format(&mut self, value: PixelFormat) {
         self.format = value.into();
     }
@@ -65,6 +99,40 @@ impl Frame {
     }
 }
 
+impl Frame {
+    pub fn nb_samples(&self) -> usize {
+        self.nb_samples.try_into().unwrap()
+    }
+
+    pub fn set_nb_samples(&mut self, value: usize) {
+        self.nb_samples = value.try_into().unwrap();
+    }
+
+    pub fn sample_fmt(&self) -> SampleFormat {
+        self.format.into()
+    }
+
+    pub fn set_sample_format(&mut self, value: SampleFormat) {
+        self.format = value.into();
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate.try_into().unwrap()
+    }
+
+    pub fn set_sample_rate(&mut self, value: u32) {
+        self.sample_rate = value.try_into().unwrap();
+    }
+
+    pub fn ch_layout(&self) -> ChannelLayout {
+        (&self.ch_layout as *const AVChannelLayout).into()
+    }
+
+    pub fn set_ch_layout(&mut self, value: &ChannelLayout) -> Result<(), Error> {
+        value.copy_to(&mut self.ch_layout)
+    }
+}
+
 impl Frame {
     /// Utility function to get the num of pixels in the frame
     pub fn pixel_count(&self) -> usize {
@@ -97,9 +165,21 @@ impl TryFrom<&Context> for Frame {
 
     fn try_from(context: &Context) -> Result<Self, Self::Error> {
         let mut frame = Self::new();
-        frame.set_format(context.pix_fmt());
-        frame.set_width(context.width());
-        frame.set_height(context.height());
+        match context.codec_type() {
+            MediaType::Audio => {
+                frame.set_sample_format(context.sample_fmt());
+                frame.set_sample_rate(context.sample_rate());
+                frame.set_ch_layout(&context.ch_layout())?;
+                frame.set_nb_samples(context.samples_per_frame());
+            }
+            _ => {
+                frame.set_format(context.pix_fmt());
+                frame.set_width(context.width());
+                frame.set_height(context.height());
+                frame.set_colorspace(context.colorspace());
+                frame.set_color_range(context.color_range());
+            }
+        }
         frame.get_buffer()?;
         Ok(frame)
     }
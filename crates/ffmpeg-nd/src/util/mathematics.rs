@@ -2,13 +2,31 @@ use super::rational::Rational;
 use ffi::*;
 use std::cmp::Ordering;
 
-pub fn compare_ts(ts_a: u32, tb_a: Rational, ts_b: u32, tb_b: Rational) -> Ordering {
-    match unsafe { av_compare_ts(ts_a.into(), tb_a.into(), ts_b.into(), tb_b.into()) }
-        .try_into()
-        .unwrap()
-    {
+pub fn compare_ts(ts_a: i64, tb_a: Rational, ts_b: i64, tb_b: Rational) -> Ordering {
+    match unsafe { av_compare_ts(ts_a, tb_a.into(), ts_b, tb_b.into()) } {
         0 => Ordering::Equal,
         n if n < 0 => Ordering::Less,
         _ => Ordering::Greater,
     }
 }
+
+/// Convert `ts` from `tb_src` to `tb_dst` units, rounding to the nearest.
+pub fn rescale(ts: i64, tb_src: Rational, tb_dst: Rational) -> i64 {
+    unsafe { av_rescale_q(ts, tb_src.into(), tb_dst.into()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let ms = Rational::new(1, 1000);
+        let mpeg = Rational::new(1, 90000);
+        assert_eq!(compare_ts(-1, ms, 0, mpeg), Ordering::Less);
+        assert_eq!(compare_ts(1, ms, 90, mpeg), Ordering::Equal);
+        assert_eq!(compare_ts(2, ms, 90, mpeg), Ordering::Greater);
+        assert_eq!(rescale(3, ms, mpeg), 270);
+        assert_eq!(rescale(1, mpeg, ms), 0);
+    }
+}
@@ -2,7 +2,7 @@
 
 use ffi::*;
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum MediaType {
     Unknown,
     Video,
@@ -1,11 +1,58 @@
 #![allow(non_upper_case_globals)]
 use ffi::*;
 
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum PixelFormat {
     None,
     RGB24,
     YUV420P,
     YUV444P,
+    /// Full range YUV420P, as decoded from JPEG.
+    YUVJ420P,
+    YUV422P,
+    /// Y plane followed by one plane of interleaved U and V.
+    NV12,
+    YUV420P10LE,
+    /// Any pixel format without a dedicated variant, e.g. of a decoded frame.
+    Other(AVPixelFormat),
+}
+
+impl PixelFormat {
+    /// `(bytes per row, rows)` of each plane of a tightly packed `width` x `height` image.
+    /// Empty for `Other`, whose layout is not known here.
+    pub fn planes(&self, width: usize, height: usize) -> Vec<(usize, usize)> {
+        // Chroma planes round up so that odd sizes keep their last column and row.
+        let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+        match self {
+            PixelFormat::None | PixelFormat::Other(_) => vec![],
+            PixelFormat::RGB24 => vec![(width * 3, height)],
+            PixelFormat::YUV420P | PixelFormat::YUVJ420P => vec![
+                (width, height),
+                (chroma_width, chroma_height),
+                (chroma_width, chroma_height),
+            ],
+            PixelFormat::YUV444P => vec![(width, height); 3],
+            PixelFormat::YUV422P => vec![
+                (width, height),
+                (chroma_width, height),
+                (chroma_width, height),
+            ],
+            PixelFormat::NV12 => vec![(width, height), (chroma_width * 2, chroma_height)],
+            PixelFormat::YUV420P10LE => vec![
+                (width * 2, height),
+                (chroma_width * 2, chroma_height),
+                (chroma_width * 2, chroma_height),
+            ],
+        }
+    }
+
+    /// Size in bytes of a tightly packed `width` x `height` image.
+    pub fn buffer_size(&self, width: usize, height: usize) -> usize {
+        self.planes(width, height)
+            .iter()
+            .map(|(row_bytes, rows)| row_bytes * rows)
+            .sum()
+    }
 }
 
 impl From<AVPixelFormat> for PixelFormat {
@@ -15,7 +62,11 @@ impl From<AVPixelFormat> for PixelFormat {
             AVPixelFormat_AV_PIX_FMT_RGB24 => PixelFormat::RGB24,
             AVPixelFormat_AV_PIX_FMT_YUV420P => PixelFormat::YUV420P,
             AVPixelFormat_AV_PIX_FMT_YUV444P => PixelFormat::YUV444P,
-            _ => panic!("Unsupported pixel format"),
+            AVPixelFormat_AV_PIX_FMT_YUVJ420P => PixelFormat::YUVJ420P,
+            AVPixelFormat_AV_PIX_FMT_YUV422P => PixelFormat::YUV422P,
+            AVPixelFormat_AV_PIX_FMT_NV12 => PixelFormat::NV12,
+            AVPixelFormat_AV_PIX_FMT_YUV420P10LE => PixelFormat::YUV420P10LE,
+            _ => PixelFormat::Other(value),
         }
     }
 }
@@ -27,6 +78,11 @@ impl Into<AVPixelFormat> for PixelFormat {
             PixelFormat::RGB24 => AVPixelFormat_AV_PIX_FMT_RGB24,
             PixelFormat::YUV420P => AVPixelFormat_AV_PIX_FMT_YUV420P,
             PixelFormat::YUV444P => AVPixelFormat_AV_PIX_FMT_YUV444P,
+            PixelFormat::YUVJ420P => AVPixelFormat_AV_PIX_FMT_YUVJ420P,
+            PixelFormat::YUV422P => AVPixelFormat_AV_PIX_FMT_YUV422P,
+            PixelFormat::NV12 => AVPixelFormat_AV_PIX_FMT_NV12,
+            PixelFormat::YUV420P10LE => AVPixelFormat_AV_PIX_FMT_YUV420P10LE,
+            PixelFormat::Other(value) => value,
         }
     }
 }
@@ -42,6 +98,35 @@ impl Default for PixelFormat {
             Name::LibX264 => PixelFormat::YUV420P,
             #[cfg(feature = "openh264")]
             Name::LibOpenH264 => PixelFormat::YUV420P,
+            _ => PixelFormat::None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        assert_eq!(PixelFormat::RGB24.buffer_size(320, 240), 320 * 240 * 3);
+        assert_eq!(
+            PixelFormat::YUV420P.buffer_size(320, 240),
+            320 * 240 * 3 / 2
+        );
+        assert_eq!(PixelFormat::YUV420P.planes(5, 3)[1], (3, 2));
+        assert_eq!(PixelFormat::YUV444P.buffer_size(5, 3), 5 * 3 * 3);
+        assert_eq!(PixelFormat::NV12.buffer_size(320, 240), 320 * 240 * 3 / 2);
+        assert_eq!(
+            PixelFormat::YUV420P10LE.buffer_size(320, 240),
+            320 * 240 * 3
+        );
+
+        let gbrp = PixelFormat::from(AVPixelFormat_AV_PIX_FMT_GBRP);
+        assert_eq!(gbrp, PixelFormat::Other(AVPixelFormat_AV_PIX_FMT_GBRP));
+        assert_eq!(
+            Into::<AVPixelFormat>::into(gbrp),
+            AVPixelFormat_AV_PIX_FMT_GBRP
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use ffi::*;
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
 pub struct Rational {
     pub num: u32,
     pub den: u32,
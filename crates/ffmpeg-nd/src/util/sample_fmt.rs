@@ -0,0 +1,85 @@
+#![allow(non_upper_case_globals)]
+use ffi::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SampleFormat {
+    None,
+    U8,
+    S16,
+    S32,
+    FLT,
+    DBL,
+    U8P,
+    S16P,
+    S32P,
+    FLTP,
+    DBLP,
+    /// Any sample format without a dedicated variant, e.g. S64 of a decoded WAV.
+    Other(AVSampleFormat),
+}
+
+impl SampleFormat {
+    pub fn bytes_per_sample(&self) -> usize {
+        unsafe { av_get_bytes_per_sample((*self).into()) as usize }
+    }
+
+    pub fn is_planar(&self) -> bool {
+        unsafe { av_sample_fmt_is_planar((*self).into()) != 0 }
+    }
+}
+
+impl From<AVSampleFormat> for SampleFormat {
+    fn from(value: AVSampleFormat) -> Self {
+        match value {
+            AVSampleFormat_AV_SAMPLE_FMT_NONE => SampleFormat::None,
+            AVSampleFormat_AV_SAMPLE_FMT_U8 => SampleFormat::U8,
+            AVSampleFormat_AV_SAMPLE_FMT_S16 => SampleFormat::S16,
+            AVSampleFormat_AV_SAMPLE_FMT_S32 => SampleFormat::S32,
+            AVSampleFormat_AV_SAMPLE_FMT_FLT => SampleFormat::FLT,
+            AVSampleFormat_AV_SAMPLE_FMT_DBL => SampleFormat::DBL,
+            AVSampleFormat_AV_SAMPLE_FMT_U8P => SampleFormat::U8P,
+            AVSampleFormat_AV_SAMPLE_FMT_S16P => SampleFormat::S16P,
+            AVSampleFormat_AV_SAMPLE_FMT_S32P => SampleFormat::S32P,
+            AVSampleFormat_AV_SAMPLE_FMT_FLTP => SampleFormat::FLTP,
+            AVSampleFormat_AV_SAMPLE_FMT_DBLP => SampleFormat::DBLP,
+            other => SampleFormat::Other(other),
+        }
+    }
+}
+
+impl Into<AVSampleFormat> for SampleFormat {
+    fn into(self) -> AVSampleFormat {
+        match self {
+            SampleFormat::None => AVSampleFormat_AV_SAMPLE_FMT_NONE,
+            SampleFormat::U8 => AVSampleFormat_AV_SAMPLE_FMT_U8,
+            SampleFormat::S16 => AVSampleFormat_AV_SAMPLE_FMT_S16,
+            SampleFormat::S32 => AVSampleFormat_AV_SAMPLE_FMT_S32,
+            SampleFormat::FLT => AVSampleFormat_AV_SAMPLE_FMT_FLT,
+            SampleFormat::DBL => AVSampleFormat_AV_SAMPLE_FMT_DBL,
+            SampleFormat::U8P => AVSampleFormat_AV_SAMPLE_FMT_U8P,
+            SampleFormat::S16P => AVSampleFormat_AV_SAMPLE_FMT_S16P,
+            SampleFormat::S32P => AVSampleFormat_AV_SAMPLE_FMT_S32P,
+            SampleFormat::FLTP => AVSampleFormat_AV_SAMPLE_FMT_FLTP,
+            SampleFormat::DBLP => AVSampleFormat_AV_SAMPLE_FMT_DBLP,
+            SampleFormat::Other(value) => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        assert_eq!(SampleFormat::S16.bytes_per_sample(), 2);
+        assert_eq!(SampleFormat::FLTP.bytes_per_sample(), 4);
+        assert!(SampleFormat::FLTP.is_planar());
+        assert!(!SampleFormat::FLT.is_planar());
+
+        let s64 = SampleFormat::from(AVSampleFormat_AV_SAMPLE_FMT_S64P);
+        assert_eq!(s64, SampleFormat::Other(AVSampleFormat_AV_SAMPLE_FMT_S64P));
+        assert_eq!(s64.bytes_per_sample(), 8);
+        assert!(s64.is_planar());
+    }
+}